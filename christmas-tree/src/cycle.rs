@@ -0,0 +1,262 @@
+//! Cycle detection for simulations that eventually loop, so that asking for the state after a
+//! ridiculous amount of iterations doesn't require running all of them.
+//!
+//! The state at iteration `n` is the result of applying the step function `n` times to the
+//! initial state (so iteration `0` is the initial state itself).
+
+use std::{collections::HashMap, hash::Hash};
+
+/// The sequence of states is `start` states long before entering a loop of `period` states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Smallest iteration that has the same state as iteration `n`.
+    pub fn equivalent(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+}
+
+/// Result of [`simulate`] and [`simulate_brent`].
+#[derive(Debug, Clone)]
+pub struct Simulation<T> {
+    /// State at the requested iteration.
+    pub state: T,
+
+    /// `None` if the target was reached before any state repeated.
+    pub cycle: Option<Cycle>,
+}
+
+fn nth<T: Clone>(initial: &T, mut step: impl FnMut(&T) -> T, n: usize) -> T {
+    let mut state = initial.clone();
+    for _ in 0..n {
+        state = step(&state);
+    }
+
+    state
+}
+
+/// Floyd's tortoise and hare. Only needs to keep two states around, at the cost of calling `step`
+/// about three times as much as [`hashed`].
+pub fn floyd<T: PartialEq + Clone>(initial: T, step: impl Fn(&T) -> T) -> Cycle {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut period = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// Brent's algorithm. Same memory as [`floyd`] but fewer calls to `step`.
+pub fn brent<T: PartialEq + Clone>(initial: T, step: impl Fn(&T) -> T) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+
+        hare = step(&hare);
+        period += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = nth(&initial, &step, period);
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// Like [`simulate`], but finds the cycle with [`brent`] so only a couple of states are kept around.
+/// The cycle is only `None` if the target was reached before Brent's algorithm found it.
+pub fn simulate_brent<T: PartialEq + Clone>(
+    initial: T,
+    step: impl Fn(&T) -> T,
+    n: usize,
+) -> Simulation<T> {
+    if n == 0 {
+        return Simulation {
+            state: initial,
+            cycle: None,
+        };
+    }
+
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+
+    // `i` is the iteration of the hare
+    for i in 1.. {
+        if tortoise == hare {
+            break;
+        }
+
+        if i == n {
+            return Simulation {
+                state: hare,
+                cycle: None,
+            };
+        }
+
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+
+        hare = step(&hare);
+        period += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = nth(&initial, &step, period);
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    // The hare found the loop before reaching `n`, so `n` is already past the start
+    let cycle = Cycle { start, period };
+    Simulation {
+        state: nth(&tortoise, &step, cycle.equivalent(n) - start),
+        cycle: Some(cycle),
+    }
+}
+
+/// Remembers every state it has seen, so `step` is called exactly once per distinct state.
+pub fn hashed<T: Hash + Eq + Clone>(initial: T, step: impl Fn(&T) -> T) -> Cycle {
+    let mut seen = HashMap::new();
+    let mut state = initial;
+
+    for i in 0.. {
+        if let Some(start) = seen.insert(state.clone(), i) {
+            return Cycle {
+                start,
+                period: i - start,
+            };
+        }
+
+        state = step(&state);
+    }
+
+    unreachable!()
+}
+
+/// Runs the simulation until iteration `n`, skipping ahead as soon as a repeated state is found.
+pub fn simulate<T: Hash + Eq + Clone>(
+    initial: T,
+    mut step: impl FnMut(&T) -> T,
+    n: usize,
+) -> Simulation<T> {
+    let mut seen = HashMap::new();
+    let mut history = Vec::new();
+    let mut state = initial;
+
+    for i in 0..n {
+        if let Some(&start) = seen.get(&state) {
+            let cycle = Cycle {
+                start,
+                period: i - start,
+            };
+
+            return Simulation {
+                state: history.swap_remove(cycle.equivalent(n)),
+                cycle: Some(cycle),
+            };
+        }
+
+        seen.insert(state.clone(), i);
+        history.push(state.clone());
+        state = step(&state);
+    }
+
+    Simulation { state, cycle: None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 -> ...
+    fn step(x: &u32) -> u32 {
+        if *x == 6 {
+            3
+        } else {
+            x + 1
+        }
+    }
+
+    const CYCLE: Cycle = Cycle {
+        start: 3,
+        period: 4,
+    };
+
+    #[test]
+    fn detectors_agree() {
+        assert_eq!(floyd(0, step), CYCLE);
+        assert_eq!(brent(0, step), CYCLE);
+        assert_eq!(hashed(0, step), CYCLE);
+    }
+
+    #[test]
+    fn simulate_skips_ahead() {
+        let result = simulate(0, step, 1_000_000_001);
+        assert_eq!(result.state, 5);
+        assert_eq!(result.cycle, Some(CYCLE));
+
+        let result = simulate(0, step, 2);
+        assert_eq!(result.state, 2);
+        assert_eq!(result.cycle, None);
+    }
+
+    #[test]
+    fn simulate_brent_matches_simulate() {
+        for n in (0..30).chain([1_000_000_001]) {
+            let expected = simulate(0, step, n).state;
+            assert_eq!(simulate_brent(0, step, n).state, expected, "{n}");
+        }
+
+        assert_eq!(simulate_brent(0, step, 1_000_000_001).cycle, Some(CYCLE));
+        assert_eq!(simulate_brent(0, step, 2).cycle, None);
+    }
+}
@@ -8,7 +8,7 @@ pub struct Day {
 
 pub fn get(day: u32) -> Day {
     cache::get_data(day).unwrap_or_else(|| {
-        println!("Fetching data for day {day}");
+        tracing::info!("Fetching data for day {day}");
         fetch_and_cache(day, &get_session_token_from_env())
     })
 }
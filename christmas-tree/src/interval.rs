@@ -0,0 +1,263 @@
+//! Sets of integers represented as sorted, disjoint half-open ranges.
+//!
+//! Useful when a puzzle asks to push whole ranges of values through some transformation instead
+//! of every value one by one (e.g. day 5's almanac maps).
+
+use std::ops::Range;
+
+pub type Interval = Range<i64>;
+
+/// A set of integers stored as a sorted list of disjoint, non-adjacent and non-empty intervals.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Amount of integers in the set.
+    pub fn len(&self) -> i64 {
+        self.intervals.iter().map(|i| i.end - i.start).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval> + '_ {
+        self.intervals.iter()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.intervals.last().map(|i| i.end - 1)
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.intervals
+            .binary_search_by(|i| {
+                if i.end <= value {
+                    std::cmp::Ordering::Less
+                } else if i.start > value {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+
+        // Every interval that overlaps or touches the new one gets merged into it.
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+
+        let merged = match (
+            self.intervals[first..last].first(),
+            self.intervals[..last].last(),
+        ) {
+            (Some(head), Some(tail)) => head.start.min(interval.start)..tail.end.max(interval.end),
+            _ => interval,
+        };
+
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut output = self.clone();
+        for interval in other.iter() {
+            output.insert(interval.clone());
+        }
+
+        output
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut output = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while let (Some(a), Some(b)) = (self.intervals.get(i), other.intervals.get(j)) {
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+
+            if start < end {
+                output.push(start..end);
+            }
+
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { intervals: output }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut output = Vec::new();
+        let mut holes = other.intervals.iter().peekable();
+
+        for interval in &self.intervals {
+            let mut start = interval.start;
+
+            while let Some(hole) = holes.peek() {
+                if hole.end <= start {
+                    holes.next();
+                    continue;
+                }
+
+                if hole.start >= interval.end {
+                    break;
+                }
+
+                if hole.start > start {
+                    output.push(start..hole.start);
+                }
+
+                start = hole.end;
+
+                if hole.end > interval.end {
+                    break;
+                }
+
+                holes.next();
+            }
+
+            if start < interval.end {
+                output.push(start..interval.end);
+            }
+        }
+
+        Self { intervals: output }
+    }
+
+    /// Splits every interval of the set at the given breakpoints. A breakpoint `b` means that `b - 1`
+    /// and `b` end up in different pieces.
+    pub fn split(&self, breakpoints: impl IntoIterator<Item = i64>) -> Vec<Interval> {
+        let mut breakpoints = breakpoints.into_iter().collect::<Vec<_>>();
+        breakpoints.sort_unstable();
+        breakpoints.dedup();
+
+        let mut output = Vec::new();
+
+        for interval in &self.intervals {
+            let first = breakpoints.partition_point(|&b| b <= interval.start);
+            let mut start = interval.start;
+
+            for &b in breakpoints[first..]
+                .iter()
+                .take_while(|&&b| b < interval.end)
+            {
+                output.push(start..b);
+                start = b;
+            }
+
+            output.push(start..interval.end);
+        }
+
+        output
+    }
+
+    /// Maps the set through a piecewise linear function. Each piece is a source interval and the
+    /// offset that gets added to values inside of it. Values not covered by any piece are left as
+    /// they are.
+    ///
+    /// Pieces are assumed to not overlap.
+    pub fn map_linear(&self, pieces: &[(Interval, i64)]) -> Self {
+        let sources = pieces
+            .iter()
+            .map(|(source, _)| source.clone())
+            .collect::<Self>();
+
+        let mut output = self.difference(&sources);
+
+        for (source, offset) in pieces {
+            for moved in self.intersection(&Self::from(source.clone())).iter() {
+                output.insert(moved.start + offset..moved.end + offset);
+            }
+        }
+
+        output
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        let mut output = Self::new();
+        output.insert(interval);
+        output
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> Self {
+        let mut output = Self::new();
+        for interval in iter {
+            output.insert(interval);
+        }
+
+        output
+    }
+}
+
+impl<'a> IntoIterator for &'a IntervalSet {
+    type Item = &'a Interval;
+    type IntoIter = std::slice::Iter<'a, Interval>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intervals.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[Interval]) -> IntervalSet {
+        intervals.iter().cloned().collect()
+    }
+
+    #[test]
+    fn insert_merges() {
+        let s = set(&[5..8, 0..2, 2..3, 7..10, 20..25, 12..12]);
+        assert_eq!(s.iter().cloned().collect::<Vec<_>>(), [0..3, 5..10, 20..25]);
+        assert_eq!(s.len(), 3 + 5 + 5);
+        assert!(s.contains(9) && !s.contains(10) && !s.contains(3));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[0..10, 20..30]);
+        let b = IntervalSet::from(5..25);
+
+        assert_eq!(a.union(&b), IntervalSet::from(0..30));
+        assert_eq!(a.intersection(&b), set(&[5..10, 20..25]));
+        assert_eq!(a.difference(&b), set(&[0..5, 25..30]));
+        assert_eq!(b.difference(&a), IntervalSet::from(10..20));
+        assert_eq!(
+            a.difference(&set(&[2..4, 6..8, 28..40])),
+            set(&[0..2, 4..6, 8..10, 20..28])
+        );
+    }
+
+    #[test]
+    fn split_and_map() {
+        let a = set(&[0..10, 20..30]);
+
+        assert_eq!(a.split([5, 20, 25, 40]), [0..5, 5..10, 20..25, 25..30]);
+
+        let mapped = a.map_linear(&[(5..22, 100), (28..35, -28)]);
+        assert_eq!(mapped, set(&[0..5, 22..28, 105..110, 120..122]));
+    }
+}
@@ -4,7 +4,7 @@
 //! runs them based on command line arguments.
 //!
 //! You need to have two functions named `part1` and `part2` in the scope of the macro for it to
-//! work.
+//! work. Optional extras (like a `visualize` function) can be listed after the day.
 //!
 //! # Usage
 //!
@@ -23,11 +23,19 @@
 //! ```
 //!
 
+use std::{collections::HashMap, str::FromStr, sync::OnceLock};
+
 use clap::Parser;
 
 pub use indoc::indoc;
+pub use tracing;
 
+pub mod cycle;
 mod data;
+pub mod interval;
+pub mod polynomial;
+pub mod render;
+pub mod viz;
 
 const YEAR: usize = 2023;
 
@@ -35,12 +43,103 @@ pub type Part<T> = fn(&str) -> T;
 pub struct Solution<T, U> {
     pub part1: Part<T>,
     pub part2: Part<U>,
+
+    /// Shows what the solution computed in the terminal. See [`viz`].
+    pub visualize: Option<fn(&str)>,
+
+    /// Draws what the solution computed to an image file. See [`render`].
+    pub render: Option<fn(&str) -> render::Image>,
+
+    /// Prints how the answer was found, in more detail than the answer itself.
+    pub explain: Option<fn(&str)>,
+
+    /// Interactive prompt reading commands from stdin to poke at the puzzle.
+    pub repl: Option<fn(&str)>,
+}
+
+impl<T, U> Solution<T, U> {
+    pub fn new(part1: Part<T>, part2: Part<U>) -> Self {
+        Self {
+            part1,
+            part2,
+            visualize: None,
+            render: None,
+            explain: None,
+            repl: None,
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
 struct Args {
     #[clap(short, long, value_parser = clap::value_parser!(u32).range(1..=2))]
     part: Option<u32>,
+
+    /// Visualize the solution instead of printing the answers
+    #[clap(long, default_value_t = false)]
+    visualize: bool,
+
+    /// Explain how the answers were found instead of printing them
+    #[clap(long, default_value_t = false)]
+    explain: bool,
+
+    /// Start an interactive prompt instead of printing the answers
+    #[clap(long, default_value_t = false)]
+    repl: bool,
+
+    /// Render the solution to an image file (relative paths go to `renders/dayXX/`)
+    #[clap(long, value_name = "PATH")]
+    render: Option<std::path::PathBuf>,
+
+    /// Print debug logs to stderr (`-vv` for even more)
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Tweak something about the puzzle, for days that support it (see [`param`])
+    #[clap(long = "param", value_name = "NAME=VALUE", value_parser = parse_param)]
+    params: Vec<(String, String)>,
+}
+
+fn parse_param(param: &str) -> Result<(String, String), String> {
+    let (name, value) = param
+        .split_once('=')
+        .ok_or_else(|| format!("`{param}` should look like `name=value`"))?;
+
+    Ok((name.to_string(), value.to_string()))
+}
+
+static PARAMS: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Value passed with `--param name=value`, so that days can be run with different settings than
+/// the puzzle's (like a bigger unfold factor). Always `None` in tests.
+///
+/// # Panics
+///
+/// If the value can't be parsed.
+pub fn param<T: FromStr>(name: &str) -> Option<T>
+where
+    T::Err: std::fmt::Display,
+{
+    let value = PARAMS.get()?.get(name)?;
+
+    match value.parse() {
+        Ok(value) => Some(value),
+        Err(err) => panic!("Invalid value `{value}` for `{name}`: {err}"),
+    }
+}
+
+/// Logs are off unless asked for, so that stdout only has the answers.
+fn init_tracing(verbosity: u8) {
+    let level = match verbosity {
+        0 => return,
+        1 => tracing::Level::DEBUG,
+        _ => tracing::Level::TRACE,
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .init();
 }
 
 /// Runs the solution as a binary
@@ -52,6 +151,54 @@ where
     U: std::fmt::Display,
 {
     let args = Args::parse();
+    init_tracing(args.verbose);
+    PARAMS.get_or_init(|| args.params.iter().cloned().collect());
+
+    if args.visualize {
+        match solution.visualize {
+            Some(visualize) => visualize(&data::get(day).input),
+            None => eprintln!("Day {day} doesn't have a visualization"),
+        }
+
+        return;
+    }
+
+    if args.explain {
+        match solution.explain {
+            Some(explain) => explain(&data::get(day).input),
+            None => eprintln!("Day {day} doesn't have an explanation"),
+        }
+
+        return;
+    }
+
+    if args.repl {
+        match solution.repl {
+            Some(repl) => repl(&data::get(day).input),
+            None => eprintln!("Day {day} doesn't have a prompt"),
+        }
+
+        return;
+    }
+
+    if let Some(path) = args.render {
+        let Some(render) = solution.render else {
+            eprintln!("Day {day} can't be rendered");
+            return;
+        };
+
+        let path = render::output_path(day, &path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+
+        match render(&data::get(day).input).save(&path) {
+            Ok(()) => println!("Rendered to {}", path.display()),
+            Err(err) => eprintln!("Couldn't render to {}: {err}", path.display()),
+        }
+
+        return;
+    }
 
     match args.part {
         Some(1) => println!("{}", (solution.part1)(&data::get(day).input)),
@@ -66,9 +213,14 @@ where
 
 #[macro_export]
 macro_rules! day {
-    ($day:literal) => {
+    ($day:literal $(, $extra:ident)* $(,)?) => {
         fn main() {
-            $crate::run_as_main(&$crate::Solution { part1, part2 }, $day);
+            let solution = $crate::Solution {
+                $($extra: Some($extra),)*
+                ..$crate::Solution::new(part1, part2)
+            };
+
+            $crate::run_as_main(&solution, $day);
         }
     };
 }
@@ -0,0 +1,267 @@
+//! Fitting polynomials to sequences with finite differences.
+//!
+//! A sequence of values at `x = 0, 1, 2...` comes from a polynomial of degree `d` when taking
+//! differences between consecutive values `d + 1` times leaves only zeros. The first value of each
+//! row of differences gives the polynomial in Newton's forward form, which is then turned into
+//! regular coefficients.
+//!
+//! When there's no way to check the degree, [`Polynomial::interpolate`] fits the lowest degree
+//! polynomial through any set of points with Lagrange's formula instead.
+//!
+//! Everything is done with exact rationals, so there's no rounding to worry about. Operations that
+//! could overflow return `None` when they do.
+
+use std::fmt;
+
+use num::{rational::Ratio, CheckedAdd, CheckedDiv, CheckedMul, One, Signed, Zero};
+
+pub type Rational = Ratio<i128>;
+
+/// Coefficients go from the constant term up to the highest power. The zero polynomial has no
+/// coefficients.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Polynomial {
+    coefficients: Vec<Rational>,
+}
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<Rational>) -> Self {
+        while coefficients.last().is_some_and(Zero::is_zero) {
+            coefficients.pop();
+        }
+
+        Self { coefficients }
+    }
+
+    /// Polynomial that goes through `values` at `x = 0, 1, 2...`.
+    ///
+    /// Returns `None` if the differences don't reach all zeros, which means that either the values
+    /// don't come from a polynomial or there aren't enough of them to tell (a degree `d`
+    /// polynomial needs at least `d + 2` values).
+    pub fn fit(values: &[i64]) -> Option<Self> {
+        let table = differences(values)?;
+
+        // Newton's forward form: the sum of Δᵏy₀ * x(x - 1)...(x - k + 1) / k!
+        let mut output = Self::default();
+        let mut falling = Self::new(vec![Rational::one()]);
+
+        for (k, row) in table.iter().enumerate() {
+            output = output.add(&falling.scale(Rational::from(row[0] as i128))?)?;
+            falling = falling
+                .mul_linear(Rational::from(-(k as i128)))?
+                .scale(Rational::new(1, k as i128 + 1))?;
+        }
+
+        Some(output)
+    }
+
+    /// Lowest degree polynomial that goes through every `(x, y)` point.
+    ///
+    /// Returns `None` if two points have the same `x` or if the arithmetic overflows.
+    pub fn interpolate(points: &[(i128, i128)]) -> Option<Self> {
+        let mut output = Self::default();
+
+        for (i, &(xi, yi)) in points.iter().enumerate() {
+            // Lagrange basis: 1 at xi and 0 at every other x
+            let mut basis = Self::new(vec![Rational::one()]);
+
+            for (j, &(xj, _)) in points.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+
+                let denominator = Rational::from(xi.checked_sub(xj)?);
+                basis = basis
+                    .mul_linear(Rational::from(xj.checked_neg()?))?
+                    .scale(Rational::one().checked_div(&denominator)?)?;
+            }
+
+            output = output.add(&basis.scale(Rational::from(yi))?)?;
+        }
+
+        Some(output)
+    }
+
+    /// `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn coefficients(&self) -> &[Rational] {
+        &self.coefficients
+    }
+
+    pub fn eval(&self, x: Rational) -> Rational {
+        self.checked_eval(x)
+            .expect("Overflow evaluating polynomial")
+    }
+
+    pub fn checked_eval(&self, x: Rational) -> Option<Rational> {
+        self.coefficients
+            .iter()
+            .rev()
+            .try_fold(Rational::zero(), |acc, c| {
+                acc.checked_mul(&x)?.checked_add(c)
+            })
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        let len = self.coefficients.len().max(other.coefficients.len());
+        let get = |p: &Self, i| p.coefficients.get(i).copied().unwrap_or_default();
+
+        let coefficients = (0..len)
+            .map(|i| get(self, i).checked_add(&get(other, i)))
+            .collect::<Option<_>>()?;
+
+        Some(Self::new(coefficients))
+    }
+
+    fn scale(&self, factor: Rational) -> Option<Self> {
+        let coefficients = self
+            .coefficients
+            .iter()
+            .map(|c| c.checked_mul(&factor))
+            .collect::<Option<_>>()?;
+
+        Some(Self::new(coefficients))
+    }
+
+    /// Multiplies by `(x + c)`.
+    fn mul_linear(&self, c: Rational) -> Option<Self> {
+        let mut output = vec![Rational::zero(); self.coefficients.len() + 1];
+        for (i, coefficient) in self.coefficients.iter().enumerate() {
+            output[i] = output[i].checked_add(&coefficient.checked_mul(&c)?)?;
+            output[i + 1] = output[i + 1].checked_add(coefficient)?;
+        }
+
+        Some(Self::new(output))
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms = self
+            .coefficients
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, c)| !c.is_zero())
+            .collect::<Vec<_>>();
+
+        if terms.is_empty() {
+            return write!(f, "0");
+        }
+
+        for (i, &(power, coefficient)) in terms.iter().enumerate() {
+            let sign = if coefficient.is_negative() { "-" } else { "+" };
+            match i {
+                0 if coefficient.is_negative() => write!(f, "-")?,
+                0 => {}
+                _ => write!(f, " {sign} ")?,
+            }
+
+            let magnitude = coefficient.abs();
+            match (power, magnitude.is_integer()) {
+                (0, _) => write!(f, "{magnitude}")?,
+                (_, true) if magnitude.is_one() => {}
+                (_, true) => write!(f, "{magnitude}")?,
+                (_, false) => write!(f, "({magnitude})")?,
+            }
+
+            match power {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{power}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Rows of differences, starting with the values themselves, up to the last row that isn't all
+/// zeros. `None` if no row of zeros is reached.
+pub fn differences(values: &[i64]) -> Option<Vec<Vec<i64>>> {
+    let mut table = Vec::new();
+    let mut row = values.to_vec();
+
+    while !row.is_empty() {
+        if row.iter().all(|v| *v == 0) {
+            return Some(table);
+        }
+
+        let next = row.windows(2).map(|w| w[1] - w[0]).collect();
+        table.push(row);
+        row = next;
+    }
+
+    None
+}
+
+/// Value `steps` places after the last one, or before the first one if `steps` is negative, by
+/// extending the polynomial that fits `values`. See [`Polynomial::fit`].
+pub fn extrapolate(values: &[i64], steps: i64) -> Option<i64> {
+    let x = if steps < 0 {
+        steps
+    } else {
+        values.len() as i64 - 1 + steps
+    };
+
+    let value = Polynomial::fit(values)?.checked_eval(Rational::from(x as i128))?;
+
+    // Integer inputs give integer values at every integer x
+    i64::try_from(value.to_integer()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integers(coefficients: &[i128]) -> Polynomial {
+        Polynomial::new(coefficients.iter().map(|&c| Rational::from(c)).collect())
+    }
+
+    #[test]
+    fn fit() {
+        let squares = [0, 1, 4, 9, 16];
+        assert_eq!(Polynomial::fit(&squares), Some(integers(&[0, 0, 1])));
+
+        let triangular = Polynomial::fit(&[1, 3, 6, 10, 15, 21]).unwrap();
+        assert_eq!(triangular.degree(), Some(2));
+        assert_eq!(triangular.to_string(), "(1/2)x^2 + (3/2)x + 1");
+
+        assert_eq!(Polynomial::fit(&[0, 0, 0]), Some(Polynomial::default()));
+        assert_eq!(Polynomial::fit(&[5, -2]), None);
+        assert_eq!(Polynomial::fit(&[1, 2, 4, 8, 16]), None);
+    }
+
+    #[test]
+    fn interpolate() {
+        // 2x² - 3x + 7
+        let f = |x: i128| 2 * x * x - 3 * x + 7;
+        let points = [65, 196, 327].map(|x| (x, f(x)));
+
+        let polynomial = Polynomial::interpolate(&points).unwrap();
+        assert_eq!(polynomial, integers(&[7, -3, 2]));
+        assert_eq!(
+            polynomial.eval(Rational::from(26_501_365)),
+            Rational::from(f(26_501_365))
+        );
+
+        assert_eq!(Polynomial::interpolate(&[(1, 2), (1, 3)]), None);
+        assert_eq!(
+            Polynomial::interpolate(&[(0, i128::MAX), (1, 0), (2, i128::MAX)]),
+            None
+        );
+    }
+
+    #[test]
+    fn extrapolate_both_ways() {
+        let values = [10, 13, 16, 21, 30, 45];
+
+        assert_eq!(extrapolate(&values, 1), Some(68));
+        assert_eq!(extrapolate(&values, -1), Some(5));
+        assert_eq!(extrapolate(&[0, 1, 4, 9], 10), Some(169));
+        assert_eq!(extrapolate(&[0, 1, 4, 9], -3), Some(9));
+    }
+}
@@ -0,0 +1,383 @@
+//! Exporting visualizations to image files.
+//!
+//! [`viz::Grid`]s get rasterized (each cell becomes a little square) into PNGs, or into animated
+//! GIFs when there are multiple frames. Vector data, like polygons or trajectories, is better
+//! exported as an [`Svg`].
+//!
+//! Days opt in by having a `render` function that returns an [`Image`] and passing it to
+//! [`crate::day`]. Then `--render <path>` writes it to `renders/dayXX/<path>` in the workspace
+//! (unless the path is absolute). The extension of the path picks the format.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use crate::viz::{self, Cell, Color};
+
+/// Size in pixels of each grid cell when rasterizing.
+const CELL_SIZE: usize = 8;
+
+/// Colors of cells without a background, and of glyphs without a foreground.
+const DEFAULT_BACKGROUND: [u8; 3] = [24, 24, 24];
+const DEFAULT_FOREGROUND: [u8; 3] = [170, 170, 170];
+
+/// Delay between animation frames, in hundredths of a second.
+const FRAME_DELAY: u16 = 5;
+
+pub enum Image {
+    Grid(viz::Grid),
+    Animation(Vec<viz::Grid>),
+    Svg(Svg),
+}
+
+impl Image {
+    /// Writes the image with the format given by the extension of `path` (`png`, `gif` or `svg`).
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+        match (self, extension) {
+            (Image::Grid(grid), "png") => write_png(grid, path),
+            (Image::Grid(grid), "gif") => write_gif(std::slice::from_ref(grid), path),
+            (Image::Animation(frames), "gif") => write_gif(frames, path),
+            (Image::Svg(svg), "svg") => std::fs::write(path, svg.to_string()),
+            (image, _) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Can't save {} as `.{extension}` (try `.{}`)",
+                    image.kind(),
+                    image.default_extension()
+                ),
+            )),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Image::Grid(_) => "a grid",
+            Image::Animation(_) => "an animation",
+            Image::Svg(_) => "an svg",
+        }
+    }
+
+    fn default_extension(&self) -> &'static str {
+        match self {
+            Image::Grid(_) => "png",
+            Image::Animation(_) => "gif",
+            Image::Svg(_) => "svg",
+        }
+    }
+}
+
+/// Where `--render <path>` ends up for a given day.
+pub fn output_path(day: u32, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+
+        workspace
+            .join("renders")
+            .join(format!("day{day:0>2}"))
+            .join(path)
+    }
+}
+
+fn cell_pixels(cell: &Cell) -> ([u8; 3], Option<[u8; 3]>) {
+    let style = cell.style;
+
+    let base = style.background.map_or(DEFAULT_BACKGROUND, |c| c.rgb());
+    let glyph = match cell.char {
+        ' ' | '.' => style.foreground.map(|c| c.rgb()),
+        _ => Some(style.foreground.map_or(DEFAULT_FOREGROUND, |c| c.rgb())),
+    };
+
+    let dim = |rgb: [u8; 3]| if style.dim { dimmed(rgb) } else { rgb };
+
+    (dim(base), glyph.map(dim))
+}
+
+fn dimmed(rgb: [u8; 3]) -> [u8; 3] {
+    rgb.map(|c| c / 3)
+}
+
+/// Every color [`rasterize`] can output, which is few enough for a gif palette.
+fn palette() -> Vec<[u8; 3]> {
+    let mut palette = Vec::new();
+
+    let colors = Color::ALL.map(|c| c.rgb());
+    let defaults = [DEFAULT_BACKGROUND, DEFAULT_FOREGROUND];
+    for rgb in colors.into_iter().chain(defaults) {
+        for rgb in [rgb, dimmed(rgb)] {
+            if !palette.contains(&rgb) {
+                palette.push(rgb);
+            }
+        }
+    }
+
+    palette
+}
+
+/// Rasterizes the grid into RGB pixels. Each cell is filled with its background color and, if it
+/// has something drawn on it, a smaller square with its foreground color in the middle.
+pub fn rasterize(grid: &viz::Grid) -> (usize, usize, Vec<[u8; 3]>) {
+    let width = grid.width() * CELL_SIZE;
+    let height = grid.height() * CELL_SIZE;
+    let margin = CELL_SIZE / 4;
+
+    let mut pixels = vec![[0; 3]; width * height];
+
+    for ([x, y], cell) in grid.cells() {
+        let (base, glyph) = cell_pixels(cell);
+
+        for dy in 0..CELL_SIZE {
+            for dx in 0..CELL_SIZE {
+                let inner = (margin..CELL_SIZE - margin).contains(&dx)
+                    && (margin..CELL_SIZE - margin).contains(&dy);
+
+                let color = match glyph {
+                    Some(glyph) if inner => glyph,
+                    _ => base,
+                };
+
+                let px = x as usize * CELL_SIZE + dx;
+                let py = y as usize * CELL_SIZE + dy;
+                pixels[py * width + px] = color;
+            }
+        }
+    }
+
+    (width, height, pixels)
+}
+
+pub fn write_png(grid: &viz::Grid, path: &Path) -> io::Result<()> {
+    let (width, height, pixels) = rasterize(grid);
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(pixels.as_flattened()))
+        .map_err(io::Error::other)
+}
+
+/// Gifs store their dimensions in 16 bits.
+fn gif_size(pixels: usize) -> io::Result<u16> {
+    u16::try_from(pixels).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{pixels} pixels is too big for a gif"),
+        )
+    })
+}
+
+/// Frames are rasterized and encoded one at a time, since long animations don't fit in memory
+/// all at once.
+pub fn write_gif(frames: &[viz::Grid], path: &Path) -> io::Result<()> {
+    let width = frames.iter().map(|grid| grid.width()).max().unwrap_or(0) * CELL_SIZE;
+    let height = frames.iter().map(|grid| grid.height()).max().unwrap_or(0) * CELL_SIZE;
+
+    let palette = palette();
+    let indices = palette
+        .iter()
+        .enumerate()
+        .map(|(index, &rgb)| (rgb, index as u8))
+        .collect::<HashMap<_, _>>();
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = gif::Encoder::new(
+        file,
+        gif_size(width)?,
+        gif_size(height)?,
+        palette.as_flattened(),
+    )
+    .map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+
+    for grid in frames {
+        let (frame_width, frame_height, pixels) = rasterize(grid);
+        let frame = gif::Frame {
+            width: gif_size(frame_width)?,
+            height: gif_size(frame_height)?,
+            delay: FRAME_DELAY,
+            buffer: pixels.iter().map(|p| indices[p]).collect::<Vec<_>>().into(),
+            ..Default::default()
+        };
+
+        encoder.write_frame(&frame).map_err(io::Error::other)?;
+    }
+
+    Ok(())
+}
+
+/// Minimal SVG builder. The view box grows to fit everything that gets drawn, so coordinates can
+/// be whatever the puzzle uses (even if they're in the trillions).
+#[derive(Debug, Clone, Default)]
+pub struct Svg {
+    bounds: Option<[[f64; 2]; 2]>,
+    elements: Vec<String>,
+}
+
+impl Svg {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn include(&mut self, [x, y]: [f64; 2]) {
+        let [min, max] = self.bounds.get_or_insert([[x, y], [x, y]]);
+        *min = [min[0].min(x), min[1].min(y)];
+        *max = [max[0].max(x), max[1].max(y)];
+    }
+
+    fn points(&mut self, points: impl IntoIterator<Item = [f64; 2]>) -> String {
+        points
+            .into_iter()
+            .map(|point| {
+                self.include(point);
+                format!("{},{}", point[0], point[1])
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn polygon(
+        &mut self,
+        points: impl IntoIterator<Item = [f64; 2]>,
+        fill: &str,
+        stroke: &str,
+    ) -> &mut Self {
+        let points = self.points(points);
+        self.elements.push(format!(
+            r#"<polygon points="{points}" fill="{fill}" stroke="{stroke}" vector-effect="non-scaling-stroke"/>"#
+        ));
+
+        self
+    }
+
+    pub fn polyline(
+        &mut self,
+        points: impl IntoIterator<Item = [f64; 2]>,
+        stroke: &str,
+    ) -> &mut Self {
+        let points = self.points(points);
+        self.elements.push(format!(
+            r#"<polyline points="{points}" fill="none" stroke="{stroke}" vector-effect="non-scaling-stroke"/>"#
+        ));
+
+        self
+    }
+
+    pub fn line(&mut self, from: [f64; 2], to: [f64; 2], stroke: &str) -> &mut Self {
+        self.polyline([from, to], stroke)
+    }
+
+    pub fn rect(&mut self, min: [f64; 2], max: [f64; 2], fill: &str, stroke: &str) -> &mut Self {
+        self.polygon([min, [max[0], min[1]], max, [min[0], max[1]]], fill, stroke)
+    }
+
+    pub fn circle(&mut self, center: [f64; 2], radius: f64, fill: &str) -> &mut Self {
+        self.include([center[0] - radius, center[1] - radius]);
+        self.include([center[0] + radius, center[1] + radius]);
+        self.elements.push(format!(
+            r#"<circle cx="{}" cy="{}" r="{radius}" fill="{fill}"/>"#,
+            center[0], center[1]
+        ));
+
+        self
+    }
+}
+
+impl fmt::Display for Svg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [min, max] = self.bounds.unwrap_or([[0.0, 0.0], [1.0, 1.0]]);
+        let margin = (max[0] - min[0]).max(max[1] - min[1]).max(1.0) * 0.02;
+
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="800" height="800">"#,
+            min[0] - margin,
+            min[1] - margin,
+            max[0] - min[0] + 2.0 * margin,
+            max[1] - min[1] + 2.0 * margin,
+        )?;
+
+        for element in &self.elements {
+            writeln!(f, "  {element}")?;
+        }
+
+        writeln!(f, "</svg>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::viz::Color;
+
+    #[test]
+    fn rasterize_grid() {
+        let mut grid = viz::Grid::from_text("#.\n..\n.#");
+        grid.region([[1, 0]], Color::Blue);
+
+        let (width, height, pixels) = rasterize(&grid);
+        assert_eq!((width, height), (2 * CELL_SIZE, 3 * CELL_SIZE));
+        assert_eq!(pixels.len(), width * height);
+
+        let pixel = |x: usize, y: usize| pixels[y * width + x];
+        let center = CELL_SIZE / 2;
+
+        // Glyph in the middle of the cell, background on its border
+        assert_eq!(pixel(center, center), DEFAULT_FOREGROUND);
+        assert_eq!(pixel(0, 0), DEFAULT_BACKGROUND);
+
+        // Empty cells are only their background
+        assert_eq!(pixel(CELL_SIZE + center, center), Color::Blue.rgb());
+        assert_eq!(pixel(center, CELL_SIZE + center), DEFAULT_BACKGROUND);
+    }
+
+    #[test]
+    fn palette_has_every_color() {
+        let palette = palette();
+        assert!(palette.len() <= 256);
+
+        let mut grid = viz::Grid::from_text("#.#\n.#.");
+        for (i, color) in Color::ALL.into_iter().enumerate() {
+            let position = [i as i64 % 3, i as i64 / 3 % 2];
+            grid.region([position], color).highlight([position], color);
+        }
+        grid.dim([[0, 0], [1, 1]]);
+
+        let (_, _, pixels) = rasterize(&grid);
+        assert!(pixels.iter().all(|pixel| palette.contains(pixel)));
+    }
+
+    #[test]
+    fn gif_too_big() {
+        assert_eq!(gif_size(800).unwrap(), 800);
+        assert!(gif_size(70_000).is_err());
+    }
+
+    #[test]
+    fn svg_bounds() {
+        let mut svg = Svg::new();
+        svg.polyline([[0.0, 0.0], [100.0, 50.0]], "red")
+            .circle([50.0, 100.0], 10.0, "blue");
+
+        let output = svg.to_string();
+
+        // Everything fits with a 2% margin of the biggest side
+        assert!(output.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-2.2 -2.2 104.4 114.4""#
+        ));
+        assert!(output.contains(r#"<polyline points="0,0 100,50" fill="none" stroke="red""#));
+        assert!(output.trim_end().ends_with("</svg>"));
+    }
+}
@@ -0,0 +1,316 @@
+//! Terminal rendering of grid puzzles.
+//!
+//! A [`Grid`] is a rectangle of characters where each cell can be styled with ANSI colors. On top
+//! of it you can put overlays (highlighted cells, paths, colored regions) and then print it, or
+//! print a bunch of them in sequence with [`animate`].
+//!
+//! Positions are `[x, y]`, so glam's vectors can be passed with `.into()`. Positions outside of the
+//! grid are silently ignored, which makes it easy to draw things that partially go out of bounds.
+//!
+//! Days opt in by having a `visualize` function and passing it to [`crate::day`]:
+//!
+//! ```rust
+//! christmas_tree::day!(1, visualize);
+//!
+//! # fn part1(input: &str) -> i32 { 42 }
+//! # fn part2(input: &str) -> i32 { 69420 }
+//! fn visualize(input: &str) {
+//!     let mut grid = christmas_tree::viz::Grid::from_text(input);
+//!     grid.highlight([[0, 0]], christmas_tree::viz::Color::Red);
+//!     grid.print();
+//! }
+//! ```
+
+use std::{fmt, io::Write, time::Duration};
+
+pub type Position = [i64; 2];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Gray,
+}
+
+impl Color {
+    pub const ALL: [Color; 9] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+        Color::Gray,
+    ];
+
+    /// All the colors that stand out on a dark terminal, for when you need to tell regions apart.
+    pub const PALETTE: [Color; 6] = [
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+    ];
+
+    fn foreground_code(&self) -> u8 {
+        match self {
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::Gray => 90,
+        }
+    }
+
+    fn background_code(&self) -> u8 {
+        self.foreground_code() + 10
+    }
+
+    /// Approximate RGB value of the color, for drawing outside of the terminal.
+    pub fn rgb(&self) -> [u8; 3] {
+        match self {
+            Color::Black => [0, 0, 0],
+            Color::Red => [220, 50, 47],
+            Color::Green => [80, 200, 80],
+            Color::Yellow => [230, 200, 40],
+            Color::Blue => [40, 110, 230],
+            Color::Magenta => [210, 60, 200],
+            Color::Cyan => [40, 200, 210],
+            Color::White => [240, 240, 240],
+            Color::Gray => [120, 120, 120],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+}
+
+impl Style {
+    fn write_escape(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let mut codes = Vec::new();
+
+        if self.bold {
+            codes.push(1);
+        }
+
+        if self.dim {
+            codes.push(2);
+        }
+
+        codes.extend(self.foreground.map(|c| c.foreground_code()));
+        codes.extend(self.background.map(|c| c.background_code()));
+
+        if codes.is_empty() {
+            return Ok(());
+        }
+
+        let codes = codes.iter().map(u8::to_string).collect::<Vec<_>>();
+        write!(f, "\x1b[{}m", codes.join(";"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cell {
+    pub char: char,
+    pub style: Style,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![
+                Cell {
+                    char: ' ',
+                    style: Style::default()
+                };
+                width * height
+            ],
+        }
+    }
+
+    /// Grid with the characters of the puzzle input. Shorter lines get padded with spaces.
+    pub fn from_text(input: &str) -> Self {
+        let width = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let height = input.lines().count();
+
+        let mut grid = Self::new(width, height);
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                grid.set([x as i64, y as i64], c);
+            }
+        }
+
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, position: impl Into<Position>) -> Option<usize> {
+        let [x, y] = position.into();
+        let inside = (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y);
+        inside.then(|| y as usize * self.width + x as usize)
+    }
+
+    pub fn get(&self, position: impl Into<Position>) -> Option<&Cell> {
+        self.index(position).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, position: impl Into<Position>) -> Option<&mut Cell> {
+        self.index(position).map(|i| &mut self.cells[i])
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (Position, &Cell)> + '_ {
+        self.cells.iter().enumerate().map(|(i, cell)| {
+            let position = [(i % self.width) as i64, (i / self.width) as i64];
+            (position, cell)
+        })
+    }
+
+    pub fn set(&mut self, position: impl Into<Position>, char: char) -> &mut Self {
+        if let Some(cell) = self.get_mut(position) {
+            cell.char = char;
+        }
+
+        self
+    }
+
+    fn style_all<P: Into<Position>>(
+        &mut self,
+        positions: impl IntoIterator<Item = P>,
+        f: impl Fn(&mut Style),
+    ) -> &mut Self {
+        for position in positions {
+            if let Some(cell) = self.get_mut(position) {
+                f(&mut cell.style);
+            }
+        }
+
+        self
+    }
+
+    /// Colors the characters of the given cells and makes them bold.
+    pub fn highlight<P: Into<Position>>(
+        &mut self,
+        positions: impl IntoIterator<Item = P>,
+        color: Color,
+    ) -> &mut Self {
+        self.style_all(positions, |style| {
+            style.foreground = Some(color);
+            style.bold = true;
+            style.dim = false;
+        })
+    }
+
+    /// Colors the background of the given cells.
+    pub fn region<P: Into<Position>>(
+        &mut self,
+        positions: impl IntoIterator<Item = P>,
+        color: Color,
+    ) -> &mut Self {
+        self.style_all(positions, |style| style.background = Some(color))
+    }
+
+    pub fn dim<P: Into<Position>>(&mut self, positions: impl IntoIterator<Item = P>) -> &mut Self {
+        self.style_all(positions, |style| style.dim = true)
+    }
+
+    /// Draws a path through consecutive positions, replacing the characters with arrows pointing
+    /// to the next step (when they are adjacent).
+    pub fn path<P: Into<Position>>(
+        &mut self,
+        positions: impl IntoIterator<Item = P>,
+        color: Color,
+    ) -> &mut Self {
+        let positions = positions.into_iter().map(Into::into).collect::<Vec<_>>();
+
+        for window in positions.windows(2) {
+            let [[x0, y0], [x1, y1]] = [window[0], window[1]];
+            let arrow = match [x1 - x0, y1 - y0] {
+                [1, 0] => '>',
+                [-1, 0] => '<',
+                [0, 1] => 'v',
+                [0, -1] => '^',
+                _ => continue,
+            };
+
+            self.set(window[0], arrow);
+        }
+
+        self.highlight(positions, color)
+    }
+
+    pub fn print(&self) {
+        println!("{self}");
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            let mut current = Style::default();
+
+            for cell in row {
+                if cell.style != current {
+                    write!(f, "\x1b[0m")?;
+                    cell.style.write_escape(f)?;
+                    current = cell.style;
+                }
+
+                write!(f, "{}", cell.char)?;
+            }
+
+            if current != Style::default() {
+                write!(f, "\x1b[0m")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints the frames one after the other in place, waiting `delay` between each of them.
+pub fn animate(frames: impl IntoIterator<Item = Grid>, delay: Duration) {
+    let mut stdout = std::io::stdout();
+
+    for frame in frames {
+        // Clear screen and move the cursor to the top left
+        print!("\x1b[2J\x1b[H{frame}");
+        stdout.flush().unwrap();
+        std::thread::sleep(delay);
+    }
+}
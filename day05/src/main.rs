@@ -1,7 +1,12 @@
-use rayon::{prelude::*, slice::ParallelSlice};
-use std::cmp::Ordering;
+use christmas_tree::interval::IntervalSet;
+use rayon::prelude::*;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
-christmas_tree::day!(5);
+christmas_tree::day!(5, explain);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Range {
@@ -20,7 +25,6 @@ impl Range {
     }
 
     pub fn cmp(&self, key: i64) -> Ordering {
-
         let source = self.source();
         let before_end = key < source.end;
         let after_start = key >= source.start;
@@ -34,7 +38,8 @@ impl Range {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Values that aren't in any range are mapped to themselves.
+#[derive(Debug, Clone, Default)]
 struct Map {
     ranges: Vec<Range>,
 }
@@ -55,6 +60,156 @@ impl Map {
         let position = key - self.ranges[range_index].source_start;
         self.ranges[range_index].destination().start + position
     }
+
+    /// Maps whole ranges at once. Ranges get split at the boundaries of the sources, and each
+    /// piece gets shifted by the offset of the range it falls into.
+    pub fn transform(&self, keys: &IntervalSet) -> IntervalSet {
+        let pieces = self
+            .ranges
+            .iter()
+            .map(|range| (range.source(), range.destination_start - range.source_start))
+            .collect::<Vec<_>>();
+
+        keys.map_linear(&pieces)
+    }
+
+    /// Every piece of the map as a source interval and an offset, including the gaps between the
+    /// ranges (which have an offset of 0).
+    fn pieces(&self) -> Vec<(std::ops::Range<i64>, i64)> {
+        let mut output = Vec::new();
+        let mut start = i64::MIN;
+
+        for range in &self.ranges {
+            if start < range.source_start {
+                output.push((start..range.source_start, 0));
+            }
+
+            output.push((range.source(), range.destination_start - range.source_start));
+            start = range.source().end;
+        }
+
+        output.push((start..i64::MAX, 0));
+        output
+    }
+
+    fn from_pieces(pieces: impl IntoIterator<Item = (std::ops::Range<i64>, i64)>) -> Self {
+        let mut ranges: Vec<Range> = Vec::new();
+
+        for (source, offset) in pieces {
+            if offset == 0 || source.is_empty() {
+                continue;
+            }
+
+            // Merge with the previous piece if it's just a continuation of it
+            if let Some(last) = ranges.last_mut() {
+                if last.source().end == source.start
+                    && last.destination_start - last.source_start == offset
+                {
+                    last.length += source.end - source.start;
+                    continue;
+                }
+            }
+
+            ranges.push(Range {
+                destination_start: source.start + offset,
+                source_start: source.start,
+                length: source.end - source.start,
+            });
+        }
+
+        Map::from(ranges)
+    }
+
+    /// Map that is the same as applying `self` and then `next`.
+    pub fn compose(&self, next: &Map) -> Map {
+        let breakpoints = next
+            .ranges
+            .iter()
+            .flat_map(|range| [range.source().start, range.source().end])
+            .collect::<Vec<_>>();
+
+        let mut pieces = self
+            .pieces()
+            .into_iter()
+            .flat_map(|(source, offset)| {
+                let image = IntervalSet::from(source.start + offset..source.end + offset);
+
+                image
+                    .split(breakpoints.iter().copied())
+                    .into_iter()
+                    .map(move |piece| {
+                        let next_offset = next.get(piece.start) - piece.start;
+                        (
+                            piece.start - offset..piece.end - offset,
+                            offset + next_offset,
+                        )
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        pieces.sort_by_key(|(source, _)| source.start);
+
+        Map::from_pieces(pieces)
+    }
+
+    /// The inverse map, if the map is a bijection (which happens when the destinations are
+    /// exactly a rearrangement of the sources).
+    pub fn inverse(&self) -> Option<Map> {
+        let sources = self.ranges.iter().map(Range::source);
+        let destinations = self.ranges.iter().map(Range::destination);
+
+        let disjoint = destinations.clone().collect::<IntervalSet>().len()
+            == self.ranges.iter().map(|range| range.length).sum::<i64>();
+
+        if !disjoint || sources.collect::<IntervalSet>() != destinations.collect::<IntervalSet>() {
+            return None;
+        }
+
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|range| Range {
+                destination_start: range.source_start,
+                source_start: range.destination_start,
+                length: range.length,
+            })
+            .collect::<Vec<_>>();
+
+        Some(Map::from(ranges))
+    }
+
+    /// All the keys that get mapped to `value`. Works even if the map is not a bijection.
+    pub fn preimages(&self, value: i64) -> Vec<i64> {
+        let mut output = self
+            .ranges
+            .iter()
+            .filter(|range| range.destination().contains(&value))
+            .map(|range| range.source_start + value - range.destination_start)
+            .collect::<Vec<_>>();
+
+        if !self.ranges.iter().any(|r| r.source().contains(&value)) {
+            output.push(value);
+        }
+
+        output.sort_unstable();
+        output.dedup();
+        output
+    }
+}
+
+/// Prints the map in the same format as the almanac.
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for range in &self.ranges {
+            writeln!(
+                f,
+                "{} {} {}",
+                range.destination_start, range.source_start, range.length
+            )?;
+        }
+
+        Ok(())
+    }
 }
 
 peg::parser! {
@@ -72,15 +227,158 @@ peg::parser! {
         rule map() -> Map
             = ranges:range() ++ _ { Map::from(ranges) }
 
-        pub rule whole() -> (Vec<i64>, Vec<Map>)
+        rule category() -> &'input str
+            = c:$(['a'..='z' | 'A'..='Z']+) { c }
+
+        rule header() -> (&'input str, &'input str)
+            = source:category() "-to-" destination:category() _ "map:" { (source, destination) }
+
+        pub rule whole() -> (Vec<i64>, Vec<(&'input str, &'input str, Map)>)
             = seeds:("seeds:" _ seeds:number() ++ _ { seeds }) _
-                maps:(['a'..='z' | 'A'..='Z' | '-']+ _ "map:" _ map:map() _ { map })* {
+                maps:(h:header() _ map:map() _ { (h.0, h.1, map) })* {
                     (seeds, maps)
         }
     }
 }
 
-fn find_lowest_seed(seeds: impl ParallelIterator<Item = i64>, maps: &[Map]) -> Option<i64> {
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AlmanacError<'a> {
+    /// There are two maps that convert from the same category
+    DuplicateSource(&'a str),
+    /// No map converts from or to this category
+    UnknownCategory(&'a str),
+    /// The categories exist, but `to` comes before `from`
+    OutOfOrder { from: &'a str, to: &'a str },
+    /// Following the maps from `from` gets stuck at `stuck` before reaching `to`
+    Missing {
+        from: &'a str,
+        to: &'a str,
+        stuck: &'a str,
+    },
+    /// Following the maps loops back to this category
+    Cycle(&'a str),
+    /// The almanac isn't written properly
+    Syntax(peg::error::ParseError<peg::str::LineCol>),
+}
+
+impl fmt::Display for AlmanacError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateSource(category) => {
+                write!(f, "There are multiple {category}-to-X maps")
+            }
+            Self::UnknownCategory(category) => write!(f, "There is no map for {category}"),
+            Self::OutOfOrder { from, to } => {
+                write!(
+                    f,
+                    "{to} comes before {from}, so there's no {from}-to-{to} conversion"
+                )
+            }
+            Self::Missing { from, to, stuck } => write!(
+                f,
+                "Can't convert {from} to {to}, there is no map from {stuck} to anything"
+            ),
+            Self::Cycle(category) => write!(f, "Conversions loop back to {category}"),
+            Self::Syntax(error) => write!(f, "Can't parse the almanac: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for AlmanacError<'_> {}
+
+/// The maps of the almanac as a graph, where each category points to the one it converts to.
+#[derive(Debug, Clone)]
+struct Almanac<'a> {
+    seeds: Vec<i64>,
+    maps: HashMap<&'a str, (&'a str, Map)>,
+}
+
+impl<'a> Almanac<'a> {
+    pub fn parse(input: &'a str) -> Result<Self, AlmanacError<'a>> {
+        let (seeds, entries) = parser::whole(input).map_err(AlmanacError::Syntax)?;
+
+        let mut maps = HashMap::new();
+        for (source, destination, map) in entries {
+            if maps.insert(source, (destination, map)).is_some() {
+                return Err(AlmanacError::DuplicateSource(source));
+            }
+        }
+
+        Ok(Self { seeds, maps })
+    }
+
+    fn is_known(&self, category: &str) -> bool {
+        self.maps.contains_key(category) || self.maps.values().any(|(to, _)| *to == category)
+    }
+
+    /// Maps to go through, in order, to convert from one category to another.
+    pub fn chain(&self, from: &'a str, to: &'a str) -> Result<Vec<&Map>, AlmanacError<'a>> {
+        let steps = self.steps(from, to)?;
+        Ok(steps.into_iter().map(|(_, map)| map).collect())
+    }
+
+    /// Same as [`Almanac::chain`], along with the category each map converts to.
+    pub fn steps(
+        &self,
+        from: &'a str,
+        to: &'a str,
+    ) -> Result<Vec<(&'a str, &Map)>, AlmanacError<'a>> {
+        for category in [from, to] {
+            if !self.is_known(category) {
+                return Err(AlmanacError::UnknownCategory(category));
+            }
+        }
+
+        self.follow(from, to).map_err(|error| match error {
+            AlmanacError::Missing { .. } if self.follow(to, from).is_ok() => {
+                AlmanacError::OutOfOrder { from, to }
+            }
+            error => error,
+        })
+    }
+
+    fn follow(&self, from: &'a str, to: &'a str) -> Result<Vec<(&'a str, &Map)>, AlmanacError<'a>> {
+        let mut output = Vec::new();
+        let mut visited = HashSet::from([from]);
+        let mut current = from;
+
+        while current != to {
+            let Some((next, map)) = self.maps.get(current) else {
+                return Err(AlmanacError::Missing {
+                    from,
+                    to,
+                    stuck: current,
+                });
+            };
+
+            if !visited.insert(next) {
+                return Err(AlmanacError::Cycle(next));
+            }
+
+            output.push((*next, map));
+            current = next;
+        }
+
+        Ok(output)
+    }
+
+    /// A single map that converts from one category to another.
+    pub fn path(&self, from: &'a str, to: &'a str) -> Result<Map, AlmanacError<'a>> {
+        let chain = self.chain(from, to)?;
+        Ok(chain
+            .into_iter()
+            .fold(Map::default(), |output, map| output.compose(map)))
+    }
+
+    pub fn seed_ranges(&self) -> IntervalSet {
+        self.seeds
+            .chunks(2)
+            .map(|chunk| chunk[0]..chunk[0] + chunk[1])
+            .collect()
+    }
+}
+
+fn find_lowest_seed(seeds: impl ParallelIterator<Item = i64>, maps: &[&Map]) -> Option<i64> {
     seeds
         .map(|seed| {
             let mut value = seed;
@@ -93,18 +391,77 @@ fn find_lowest_seed(seeds: impl ParallelIterator<Item = i64>, maps: &[Map]) -> O
 }
 
 fn part1(input: &str) -> i64 {
-    let (seeds, maps) = parser::whole(input).unwrap();
+    let almanac = Almanac::parse(input).unwrap();
+    let maps = almanac.chain("seed", "location").unwrap();
+
+    find_lowest_seed(almanac.seeds.par_iter().copied(), &maps).unwrap()
+}
+
+/// Pushes the seed ranges through the maps as whole ranges, so it doesn't matter how many seeds
+/// there are.
+fn part2(input: &str) -> i64 {
+    let almanac = Almanac::parse(input).unwrap();
+    let maps = almanac.chain("seed", "location").unwrap();
+
+    let locations = maps
+        .iter()
+        .fold(almanac.seed_ranges(), |ranges, map| map.transform(&ranges));
+
+    locations.min().unwrap()
+}
+
+fn explain(input: &str) {
+    let parsed = match Almanac::parse(input) {
+        Ok(almanac) => almanac,
+        Err(error) => return println!("Invalid almanac: {error}"),
+    };
+
+    let steps = match parsed.steps("seed", "location") {
+        Ok(steps) => steps,
+        Err(error) => return println!("Invalid almanac: {error}"),
+    };
+
+    let categories = std::iter::once("seed")
+        .chain(steps.iter().map(|(category, _)| *category))
+        .collect::<Vec<_>>();
+
+    println!("Categories: {}\n", categories.join(" -> "));
+
+    let almanac = parsed.path("seed", "location").unwrap();
+
+    println!("seed-to-location map:\n{almanac}");
+
+    if almanac.inverse().is_some() {
+        println!("The almanac is a bijection, every location comes from exactly one seed\n");
+    } else {
+        println!("The almanac is not a bijection, some locations come from multiple seeds\n");
+    }
+
+    for seed in &parsed.seeds {
+        println!("Seed {seed} goes to location {}", almanac.get(*seed));
+    }
+
+    let seed_ranges = parsed.seed_ranges();
+
+    let lowest = part2(input);
+    let origins = almanac
+        .preimages(lowest)
+        .into_iter()
+        .filter(|seed| seed_ranges.contains(*seed))
+        .collect::<Vec<_>>();
 
-    find_lowest_seed(seeds.into_par_iter(), &maps).unwrap()
+    println!("\nLowest location of the seed ranges is {lowest}, from seed {origins:?}");
 }
 
-/// This implementation is very brute-forcey, but with the help of
+/// The original implementation. It's very brute-forcey, but with the help of
 /// rayon it does compute the proper solution in a bit under 10s on
 /// my machine.
-fn part2(input: &str) -> i64 {
-    let (seed_ranges, maps) = parser::whole(input).unwrap();
+#[cfg(test)]
+fn part2_brute_force(input: &str) -> i64 {
+    let almanac = Almanac::parse(input).unwrap();
+    let maps = almanac.chain("seed", "location").unwrap();
 
-    let seeds = seed_ranges.par_chunks(2).flat_map(|chunk| {
+    let seeds = almanac.seeds.par_chunks(2).flat_map(|chunk| {
         let [start, length] = chunk.try_into().unwrap();
 
         start..start + length
@@ -113,40 +470,150 @@ fn part2(input: &str) -> i64 {
     find_lowest_seed(seeds, &maps).unwrap()
 }
 
-christmas_tree::examples! {
-    r"
-        seeds: 79 14 55 13
+#[cfg(test)]
+const EXAMPLE: &str = christmas_tree::indoc! {"
+    seeds: 79 14 55 13
 
-        seed-to-soil map:
-        50 98 2
-        52 50 48
+    seed-to-soil map:
+    50 98 2
+    52 50 48
+
+    soil-to-fertilizer map:
+    0 15 37
+    37 52 2
+    39 0 15
+
+    fertilizer-to-water map:
+    49 53 8
+    0 11 42
+    42 0 7
+    57 7 4
+
+    water-to-light map:
+    88 18 7
+    18 25 70
+
+    light-to-temperature map:
+    45 77 23
+    81 45 19
+    68 64 13
+
+    temperature-to-humidity map:
+    0 69 1
+    1 0 69
+
+    humidity-to-location map:
+    60 56 37
+    56 93 4
+"};
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn part1() {
+        assert_eq!(super::part1(super::EXAMPLE), 35);
+    }
+
+    #[test]
+    fn part2() {
+        assert_eq!(super::part2(super::EXAMPLE), 46);
+    }
+}
+
+#[test]
+fn part2_matches_brute_force() {
+    // Try a bunch of windows of seeds, so that ranges start and end all over the place
+    let maps = EXAMPLE.split_once("\n\n").unwrap().1;
 
-        soil-to-fertilizer map:
-        0 15 37
-        37 52 2
-        39 0 15
+    for start in 0..100 {
+        for length in [1, 3, 17, 50] {
+            let input = format!("seeds: {start} {length}\n\n{maps}");
+            assert_eq!(part2(&input), part2_brute_force(&input), "{start} {length}");
+        }
+    }
+
+    let example = format!("seeds: 79 14 55 13 0 100 90 10\n\n{maps}");
+    assert_eq!(part2(&example), part2_brute_force(&example));
+}
 
-        fertilizer-to-water map:
-        49 53 8
-        0 11 42
-        42 0 7
-        57 7 4
+#[test]
+fn composed_map_matches_chain() {
+    let parsed = Almanac::parse(EXAMPLE).unwrap();
+    let maps = parsed.chain("seed", "location").unwrap();
+    let almanac = parsed.path("seed", "location").unwrap();
+    let inverse = almanac.inverse().expect("Example maps are bijections");
+
+    for seed in -10..150 {
+        let location = maps.iter().fold(seed, |value, map| map.get(value));
+
+        assert_eq!(almanac.get(seed), location);
+        assert_eq!(inverse.get(location), seed);
+        assert_eq!(almanac.preimages(location), [seed]);
+    }
+}
+
+#[test]
+fn conversion_paths() {
+    let example = christmas_tree::indoc! {"
+        seeds: 1
+
+        soil-to-water map:
+        0 10 5
+
+        seed-to-soil map:
+        10 0 5
 
         water-to-light map:
-        88 18 7
-        18 25 70
-
-        light-to-temperature map:
-        45 77 23
-        81 45 19
-        68 64 13
-
-        temperature-to-humidity map:
-        0 69 1
-        1 0 69
-
-        humidity-to-location map:
-        60 56 37
-        56 93 4
-    " => 35, 46,
+        100 0 5
+    "};
+
+    let almanac = Almanac::parse(example).unwrap();
+
+    assert_eq!(almanac.path("soil", "light").unwrap().get(12), 102);
+    assert_eq!(almanac.path("seed", "light").unwrap().get(3), 103);
+    assert_eq!(almanac.path("water", "water").unwrap().get(3), 3);
+
+    assert_eq!(
+        almanac.chain("light", "seed").unwrap_err(),
+        AlmanacError::OutOfOrder {
+            from: "light",
+            to: "seed"
+        }
+    );
+    assert_eq!(
+        almanac.chain("seed", "location").unwrap_err(),
+        AlmanacError::UnknownCategory("location")
+    );
+
+    let missing = "seeds: 1\n\nseed-to-soil map:\n0 0 1\n\nwater-to-light map:\n0 0 1\n";
+    let almanac = Almanac::parse(missing).unwrap();
+    assert_eq!(
+        almanac.chain("seed", "light").unwrap_err(),
+        AlmanacError::Missing {
+            from: "seed",
+            to: "light",
+            stuck: "soil"
+        }
+    );
+
+    let duplicate = "seeds: 1\n\nseed-to-soil map:\n0 0 1\n\nseed-to-water map:\n0 0 1\n";
+    assert_eq!(
+        Almanac::parse(duplicate).unwrap_err(),
+        AlmanacError::DuplicateSource("seed")
+    );
+
+    let cycle = "seeds: 1\n\nseed-to-soil map:\n0 0 1\n\nsoil-to-seed map:\n0 0 1\n\n\
+                 water-to-location map:\n0 0 1\n";
+    assert_eq!(
+        Almanac::parse(cycle)
+            .unwrap()
+            .chain("seed", "location")
+            .unwrap_err(),
+        AlmanacError::Cycle("seed")
+    );
+
+    assert!(matches!(
+        Almanac::parse("seeds: one"),
+        Err(AlmanacError::Syntax(_))
+    ));
 }
@@ -1,6 +1,78 @@
-christmas_tree::day!(6);
+christmas_tree::day!(6, explain);
 
-fn parse(input: &str) -> impl Iterator<Item = (i64, i64)> + '_ {
+/// Holding the button for `t` milliseconds makes the boat go `t * (time - t)` millimeters, and
+/// beating the record means `t * (time - t) > distance`. That's a quadratic, so the winning hold
+/// times are the integers strictly between its two roots.
+///
+/// Numbers are `i128` so that `time²` can't overflow for any input that fits in an `i64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Race {
+    time: i128,
+    distance: i128,
+}
+
+/// Range of hold times that beat the record, both ends included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bounds {
+    lowest: i128,
+    highest: i128,
+}
+
+impl Bounds {
+    fn count(&self) -> i128 {
+        self.highest - self.lowest + 1
+    }
+}
+
+impl Race {
+    fn traveled(&self, hold: i128) -> i128 {
+        hold * (self.time - hold)
+    }
+
+    fn wins(&self, hold: i128) -> bool {
+        self.traveled(hold) > self.distance
+    }
+
+    /// `time² - 4 * distance`. The race can only be won if it's positive.
+    fn discriminant(&self) -> i128 {
+        self.time * self.time - 4 * self.distance
+    }
+
+    /// The roots are `(time ± √discriminant) / 2`. The integer square root is exact, so the
+    /// smallest winning hold time is at most a step or two away from `(time - isqrt) / 2`, and the
+    /// highest one is symmetric to it.
+    fn bounds(&self) -> Option<Bounds> {
+        // The boat goes the furthest when the button is held for half the race
+        let discriminant = self.discriminant();
+        if discriminant <= 0 || !self.wins(self.time / 2) {
+            return None;
+        }
+
+        let mut lowest = (self.time - discriminant.isqrt()) / 2;
+        while !self.wins(lowest) {
+            lowest += 1;
+        }
+        while lowest > 0 && self.wins(lowest - 1) {
+            lowest -= 1;
+        }
+
+        Some(Bounds {
+            lowest,
+            highest: self.time - lowest,
+        })
+    }
+
+    fn ways_to_win(&self) -> i128 {
+        self.bounds().map_or(0, |bounds| bounds.count())
+    }
+
+    #[cfg(test)]
+    fn ways_to_win_brute_force(&self) -> i128 {
+        (0..=self.time).filter(|&hold| self.wins(hold)).count() as i128
+    }
+}
+
+fn parse(input: &str) -> impl Iterator<Item = Race> + '_ {
     let mut lines = input
         .lines()
         .map(|line| line.split_whitespace().skip(1).map(|s| s.parse().unwrap()));
@@ -8,57 +80,62 @@ fn parse(input: &str) -> impl Iterator<Item = (i64, i64)> + '_ {
     let times = lines.next().unwrap();
     let distances = lines.next().unwrap();
 
-    times.zip(distances)
+    times
+        .zip(distances)
+        .map(|(time, distance)| Race { time, distance })
 }
 
-fn parse_joined(input: &str) -> (i64, i64) {
+fn parse_joined(input: &str) -> Race {
     let mut lines = input.lines().map(|line| {
         line.split_whitespace()
             .skip(1)
             .collect::<Vec<_>>()
             .join("")
-            .parse::<i64>()
+            .parse::<i128>()
             .unwrap()
     });
 
     let time = lines.next().unwrap();
     let distance = lines.next().unwrap();
 
-    (time, distance)
+    Race { time, distance }
 }
 
-fn part1(input: &str) -> i64 {
-    parse(input)
-        .map(|(time, distance)| {
-            (1..=time)
-                .map(|t| {
-                    let remaining = time - t;
-                    let accelerated = t;
-                    accelerated * remaining
-                })
-                .filter(|&d| d > distance)
-                .count() as i64
-        })
-        .product()
+fn part1(input: &str) -> i128 {
+    parse(input).map(|race| race.ways_to_win()).product()
 }
 
-fn part2(input: &str) -> i64 {
-    let (time, distance) = parse_joined(input);
+fn part2(input: &str) -> i128 {
+    parse_joined(input).ways_to_win()
+}
 
-    let find = |t| {
-        let remaining = time - t;
-        let accelerated = t;
-        if accelerated * remaining > distance {
-            Some(t)
-        } else {
-            None
-        }
-    };
+fn explain_race(race: &Race) {
+    println!(
+        "Time {}, record {}: discriminant {}",
+        race.time,
+        race.distance,
+        race.discriminant()
+    );
 
-    let lower_bound = (1..=time).find_map(find).unwrap();
-    let upper_bound = (1..=time).rev().find_map(find).unwrap();
+    match race.bounds() {
+        Some(bounds) => println!(
+            "  Holding from {} to {} ms wins, {} ways",
+            bounds.lowest,
+            bounds.highest,
+            bounds.count()
+        ),
+        None => println!("  The record can't be beaten"),
+    }
+}
 
-    upper_bound - lower_bound + 1
+fn explain(input: &str) {
+    println!("Part 1:");
+    for race in parse(input) {
+        explain_race(&race);
+    }
+
+    println!("\nPart 2:");
+    explain_race(&parse_joined(input));
 }
 
 christmas_tree::examples! {
@@ -67,3 +144,49 @@ christmas_tree::examples! {
         Distance:  9  40  200
     " => 288, 71503,
 }
+
+#[test]
+fn closed_form_matches_enumeration() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(6);
+
+    for _ in 0..10_000 {
+        let time = rng.gen_range(0..200);
+        let distance = rng.gen_range(0..time * time / 4 + 10);
+        let race = Race { time, distance };
+
+        assert_eq!(
+            race.ways_to_win(),
+            race.ways_to_win_brute_force(),
+            "{race:?}"
+        );
+    }
+
+    // Records exactly at the peak can't be beaten, one below it can
+    let race = Race {
+        time: 10,
+        distance: 25,
+    };
+    assert_eq!(race.ways_to_win(), 0);
+    assert_eq!(
+        Race {
+            distance: 24,
+            ..race
+        }
+        .ways_to_win(),
+        1
+    );
+}
+
+#[test]
+fn huge_races() {
+    let time = i64::MAX as i128;
+    let race = Race {
+        time,
+        distance: (time / 2) * (time - time / 2) - 1,
+    };
+
+    // Only the two hold times closest to the middle beat the record
+    assert_eq!(race.ways_to_win(), 2);
+}
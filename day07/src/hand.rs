@@ -0,0 +1,319 @@
+//! Ranking hands of cards with configurable rules, so the same engine can play Camel Cards (with
+//! or without jokers) or something closer to regular poker.
+
+use std::fmt;
+
+/// Value of a card, from 2 up to 14 for aces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Value(pub u8);
+
+impl Value {
+    pub const TEN: Self = Self(10);
+    pub const JACK: Self = Self(11);
+    pub const QUEEN: Self = Self(12);
+    pub const KING: Self = Self(13);
+    pub const ACE: Self = Self(14);
+
+    pub fn new(char: char) -> Option<Self> {
+        match char {
+            '2'..='9' => Some(Self(char.to_digit(10).unwrap() as u8)),
+            'T' => Some(Self::TEN),
+            'J' => Some(Self::JACK),
+            'Q' => Some(Self::QUEEN),
+            'K' => Some(Self::KING),
+            'A' => Some(Self::ACE),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let char = match *self {
+            Self::TEN => 'T',
+            Self::JACK => 'J',
+            Self::QUEEN => 'Q',
+            Self::KING => 'K',
+            Self::ACE => 'A',
+            Self(value) => char::from_digit(value as u32, 10).unwrap(),
+        };
+
+        write!(f, "{char}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    pub fn new(char: char) -> Option<Self> {
+        match char {
+            'c' | '♣' => Some(Self::Clubs),
+            'd' | '♦' => Some(Self::Diamonds),
+            'h' | '♥' => Some(Self::Hearts),
+            's' | '♠' => Some(Self::Spades),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let char = match self {
+            Self::Clubs => 'c',
+            Self::Diamonds => 'd',
+            Self::Hearts => 'h',
+            Self::Spades => 's',
+        };
+
+        write!(f, "{char}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Card {
+    pub value: Value,
+    pub suit: Option<Suit>,
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)?;
+
+        match self.suit {
+            Some(suit) => write!(f, "{suit}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses a hand like `KTJJT` or, with suits, `KhTdJsJcTh`.
+pub fn parse_hand(input: &str) -> Vec<Card> {
+    let mut chars = input.chars().peekable();
+    let mut output = Vec::new();
+
+    while let Some(char) = chars.next() {
+        let value = Value::new(char).unwrap_or_else(|| panic!("Invalid card: {char}"));
+        let suit = chars.peek().copied().and_then(Suit::new);
+        if suit.is_some() {
+            chars.next();
+        }
+
+        output.push(Card { value, suit });
+    }
+
+    output
+}
+
+/// Categories from worst to best. Hands can have any amount of cards, so the "of a kind"
+/// categories mean at least that many (the bundles break ties between e.g. 5 and 6 of a kind).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::HighCard => "High card",
+            Self::OnePair => "One pair",
+            Self::TwoPair => "Two pair",
+            Self::ThreeOfAKind => "Three of a kind",
+            Self::Straight => "Straight",
+            Self::Flush => "Flush",
+            Self::FullHouse => "Full house",
+            Self::FourOfAKind => "Four of a kind",
+            Self::StraightFlush => "Straight flush",
+            Self::FiveOfAKind => "Five of a kind",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rules {
+    /// Values that can stand in for any other card. They are the weakest cards when breaking ties.
+    pub wild: Vec<Value>,
+    pub straights: bool,
+    /// Needs every card to have a suit, except the wild ones.
+    pub flushes: bool,
+}
+
+/// How strong a hand is. Hands compare by category, then by the sizes of their groups of equal
+/// cards, and then card by card in the order they were dealt.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rank {
+    pub category: Category,
+    pub bundles: Vec<usize>,
+    tiebreak: Vec<u8>,
+}
+
+impl Rules {
+    fn is_wild(&self, card: &Card) -> bool {
+        self.wild.contains(&card.value)
+    }
+
+    pub fn rank(&self, hand: &[Card]) -> Rank {
+        let (wild, natural): (Vec<&Card>, Vec<&Card>) =
+            hand.iter().partition(|card| self.is_wild(card));
+
+        let mut values = natural.iter().map(|card| card.value).collect::<Vec<_>>();
+        values.sort();
+
+        let mut bundles = values
+            .chunk_by(|a, b| a == b)
+            .map(|bundle| bundle.len())
+            .collect::<Vec<_>>();
+        bundles.sort_by_key(|&b| std::cmp::Reverse(b));
+
+        // Wild cards are always best used to grow the biggest group
+        match bundles.first_mut() {
+            Some(biggest) => *biggest += wild.len(),
+            None if !wild.is_empty() => bundles.push(wild.len()),
+            None => {}
+        }
+
+        let mut category = match (bundles.first(), bundles.get(1)) {
+            (Some(5..), _) => Category::FiveOfAKind,
+            (Some(4), _) => Category::FourOfAKind,
+            (Some(3), Some(2..)) => Category::FullHouse,
+            (Some(3), _) => Category::ThreeOfAKind,
+            (Some(2), Some(2)) => Category::TwoPair,
+            (Some(2), _) => Category::OnePair,
+            _ => Category::HighCard,
+        };
+
+        let straight = self.straights && is_straight(&values, hand.len());
+        let flush = self.flushes && is_flush(&natural);
+
+        let special = match (straight, flush) {
+            (true, true) => Some(Category::StraightFlush),
+            (false, true) => Some(Category::Flush),
+            (true, false) => Some(Category::Straight),
+            (false, false) => None,
+        };
+        category = category.max(special.unwrap_or(Category::HighCard));
+
+        let tiebreak = hand
+            .iter()
+            .map(|card| if self.is_wild(card) { 0 } else { card.value.0 })
+            .collect();
+
+        Rank {
+            category,
+            bundles,
+            tiebreak,
+        }
+    }
+}
+
+/// Whether the (sorted, non-wild) values can be completed with wild cards into `size` consecutive
+/// values. Aces can also be low, like in `A2345`.
+fn is_straight(values: &[Value], size: usize) -> bool {
+    if values.windows(2).any(|pair| pair[0] == pair[1]) {
+        return false;
+    }
+
+    let fits = |low: u8, high: u8| ((high - low) as usize) < size;
+
+    match (values.first(), values.last()) {
+        (Some(low), Some(high)) => {
+            let ace_low = *high == Value::ACE && values.len() > 1 && {
+                let below_ace = values[values.len() - 2];
+                fits(1, below_ace.0)
+            };
+
+            fits(low.0, high.0) || ace_low
+        }
+        _ => true,
+    }
+}
+
+/// Cards without suits (like in Camel Cards) never make a flush.
+fn is_flush(cards: &[&Card]) -> bool {
+    let Some(first) = cards.first().and_then(|card| card.suit) else {
+        return false;
+    };
+
+    cards.iter().all(|card| card.suit == Some(first))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(rules: &Rules, hand: &str) -> Category {
+        rules.rank(&parse_hand(hand)).category
+    }
+
+    #[test]
+    fn camel_cards() {
+        let rules = Rules::default();
+        assert_eq!(category(&rules, "32T3K"), Category::OnePair);
+        assert_eq!(category(&rules, "KTJJT"), Category::TwoPair);
+        assert_eq!(category(&rules, "23456"), Category::HighCard);
+
+        let jokers = Rules {
+            wild: vec![Value::JACK],
+            ..Rules::default()
+        };
+        assert_eq!(category(&jokers, "KTJJT"), Category::FourOfAKind);
+        assert_eq!(category(&jokers, "JJJJJ"), Category::FiveOfAKind);
+        assert!(jokers.rank(&parse_hand("JKKK2")) < jokers.rank(&parse_hand("QQQQ2")));
+    }
+
+    #[test]
+    fn poker() {
+        let rules = Rules {
+            straights: true,
+            flushes: true,
+            ..Rules::default()
+        };
+
+        assert_eq!(category(&rules, "2h3h4h5h6h"), Category::StraightFlush);
+        assert_eq!(category(&rules, "Ah2c3d4s5h"), Category::Straight);
+        assert_eq!(category(&rules, "QhKcAd2s3h"), Category::HighCard);
+        assert_eq!(category(&rules, "2h9h4hKh6h"), Category::Flush);
+        assert_eq!(category(&rules, "2h2c2d9s9h"), Category::FullHouse);
+
+        // Without suits there are no flushes
+        assert_eq!(category(&rules, "2345K"), Category::HighCard);
+        assert_eq!(category(&rules, "23456"), Category::Straight);
+        assert_eq!(category(&rules, "2h3h4h5h6"), Category::Straight);
+
+        let wild = Rules {
+            wild: vec![Value(2)],
+            ..rules
+        };
+        assert_eq!(category(&wild, "2c3h5h6h7h"), Category::StraightFlush);
+        assert_eq!(category(&wild, "2c2d9h9s9c"), Category::FiveOfAKind);
+    }
+
+    #[test]
+    fn other_hand_sizes() {
+        let rules = Rules {
+            straights: true,
+            ..Rules::default()
+        };
+
+        assert_eq!(category(&rules, "345"), Category::Straight);
+        assert_eq!(category(&rules, "3333333"), Category::FiveOfAKind);
+        assert_eq!(category(&rules, "333KKK2"), Category::FullHouse);
+        assert!(rules.rank(&parse_hand("3333333")) > rules.rank(&parse_hand("444444")));
+    }
+}
@@ -1,120 +1,85 @@
-christmas_tree::day!(7);
+christmas_tree::day!(7, explain);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum Value {
-    Joker,
-    Number(i32),
-    Jack,
-    Queen,
-    King,
-    Ace,
-}
-
-impl Value {
-    fn new(char: char, j_is_joker: bool) -> Self {
-        if let Some(digit) = char.to_digit(10) {
-            Value::Number(digit as i32)
-        } else {
-            match char {
-                'T' => Self::Number(10),
-                'J' if !j_is_joker => Self::Jack,
-                'J' if j_is_joker => Self::Joker,
-                'Q' => Self::Queen,
-                'K' => Self::King,
-                'A' => Self::Ace,
-                other => panic!("Invalid char: {other}"),
-            }
-        }
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-struct Rank {
-    bundles: [i32; 5],
-}
-
-impl Rank {
-    fn new(values: [Value; 5]) -> Self {
-        let mut bundles = [0; 5];
-        let mut seen = [false; 5];
-        let mut jokers = 0;
-
-        for (i, value) in values.iter().enumerate() {
-            if *value == Value::Joker {
-                jokers += 1;
-                continue;
-            }
-
-            if seen[i] {
-                continue;
-            }
-
-            let mut count = 1;
-
-            for (j, other) in values.iter().enumerate().skip(i + 1) {
-                if value == other {
-                    seen[j] = true;
-                    count += 1;
-                }
-            }
-
-            bundles[i] = count;
-        }
-
-        bundles.sort_by_key(|&b| std::cmp::Reverse(b));
-        
-        bundles[0] += jokers;
-
-        assert_eq!(bundles.iter().sum::<i32>(), 5);
+mod hand;
 
-        Rank { bundles }
-    }
-}
+use hand::{Card, Rank, Rules, Value};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Bid {
+    hand: Vec<Card>,
     rank: Rank,
-    hand: [Value; 5],
     amount: i64,
 }
 
-fn consume<T>(mut iter: impl Iterator<Item = T>) -> [T; 5] {
-    [
-        iter.next().unwrap(),
-        iter.next().unwrap(),
-        iter.next().unwrap(),
-        iter.next().unwrap(),
-        iter.next().unwrap(),
-    ]
-}
-
-fn solve(input: &str, j_is_joker: bool) -> i64 {
-    let mut bids = input
+fn parse(input: &str, rules: &Rules) -> Vec<Bid> {
+    input
         .lines()
         .map(|line| {
             let mut parts = line.split_whitespace();
-            let chars = parts.next().unwrap().chars();
-
-            let hand = consume(chars.map(|c| Value::new(c, j_is_joker)));
-            let rank = Rank::new(hand);
+            let hand = hand::parse_hand(parts.next().unwrap());
+            let rank = rules.rank(&hand);
             let amount = parts.next().unwrap().parse().unwrap();
-            Bid { rank, hand, amount }
+            Bid { hand, rank, amount }
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
+
+/// Bids from the weakest hand to the strongest one.
+fn ranked(input: &str, rules: &Rules) -> Vec<Bid> {
+    let mut bids = parse(input, rules);
+    bids.sort_by(|a, b| a.rank.cmp(&b.rank));
+    bids
+}
 
-    bids.sort();
-    bids.iter()
+fn solve(input: &str, rules: &Rules) -> i64 {
+    ranked(input, rules)
+        .iter()
         .enumerate()
         .map(|(i, bid)| (i as i64 + 1) * bid.amount)
         .sum()
 }
 
+fn jokers() -> Rules {
+    Rules {
+        wild: vec![Value::JACK],
+        ..Rules::default()
+    }
+}
+
 fn part1(input: &str) -> i64 {
-    solve(input, false)
+    solve(input, &Rules::default())
 }
 
 fn part2(input: &str) -> i64 {
-    solve(input, true)
+    solve(input, &jokers())
+}
+
+fn explain(input: &str) {
+    for (name, rules) in [
+        ("Part 1", Rules::default()),
+        ("Part 2 (J is wild)", jokers()),
+    ] {
+        println!("{name}:");
+
+        for (i, bid) in ranked(input, &rules).iter().enumerate() {
+            let hand = bid
+                .hand
+                .iter()
+                .map(|card| card.to_string())
+                .collect::<String>();
+
+            println!(
+                "  {:>4}. {hand}  {:<15}  {} * {} = {}",
+                i + 1,
+                bid.rank.category.to_string(),
+                bid.amount,
+                i + 1,
+                bid.amount * (i as i64 + 1)
+            );
+        }
+
+        println!();
+    }
 }
 
 christmas_tree::examples! {
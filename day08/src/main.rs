@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-christmas_tree::day!(8);
+use christmas_tree::{cycle, tracing};
+use num::Integer;
+
+christmas_tree::day!(8, explain);
 
 type Entry<'a> = (&'a str, &'a str);
 type Map<'a> = HashMap<&'a str, Entry<'a>>;
@@ -50,10 +53,163 @@ fn part1(input: &str) -> i64 {
     unreachable!()
 }
 
+fn ends_with(node: &str, char: u8) -> bool {
+    node.as_bytes()[2] == char
+}
+
+/// Steps at which a ghost stands on a Z node. The ghost's state is its node together with the
+/// position in the instructions, so it eventually loops: after `cycle.start` steps it repeats the
+/// same `cycle.period` states forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Ghost<'a> {
+    start: &'a str,
+    cycle: cycle::Cycle,
+    /// Z steps before the loop starts, which only happen once.
+    prefix_hits: Vec<i64>,
+    /// Z steps during the first time through the loop. Each of them happens again every period.
+    cycle_hits: Vec<i64>,
+}
+
+impl<'a> Ghost<'a> {
+    fn new(start: &'a str, instructions: &[u8], edges: &Map<'a>) -> Self {
+        let step = |&(node, i): &(&'a str, usize)| {
+            (
+                get_next(node, instructions, i, edges),
+                (i + 1) % instructions.len(),
+            )
+        };
+
+        let cycle = cycle::hashed((start, 0), step);
+
+        let mut prefix_hits = Vec::new();
+        let mut cycle_hits = Vec::new();
+        let mut state = (start, 0);
+
+        for i in 0..cycle.start + cycle.period {
+            if ends_with(state.0, b'Z') {
+                if i < cycle.start {
+                    prefix_hits.push(i as i64);
+                } else {
+                    cycle_hits.push(i as i64);
+                }
+            }
+
+            state = step(&state);
+        }
+
+        Self {
+            start,
+            cycle,
+            prefix_hits,
+            cycle_hits,
+        }
+    }
+
+    fn period(&self) -> i64 {
+        self.cycle.period as i64
+    }
+
+    fn is_at_z(&self, step: i64) -> bool {
+        let equivalent = self.cycle.equivalent(step as usize) as i64;
+        self.prefix_hits.contains(&equivalent) || self.cycle_hits.contains(&equivalent)
+    }
+
+    /// The LCM shortcut assumes that the ghost is on a Z node exactly at the multiples of the
+    /// first step where it reaches one.
+    fn is_multiples_of_first_hit(&self) -> bool {
+        let Some(&first) = self.prefix_hits.first().or(self.cycle_hits.first()) else {
+            return false;
+        };
+
+        let end = (self.cycle.start + self.cycle.period) as i64;
+        self.period() % first == 0 && (1..end).all(|i| self.is_at_z(i) == (i % first == 0))
+    }
+}
+
+/// Combines `t ≡ a (mod m)` and `t ≡ b (mod n)` into a single congruence, if there's any `t` that
+/// satisfies both. The moduli don't need to be coprime.
+fn crt((a, m): (i128, i128), (b, n): (i128, i128)) -> Option<(i128, i128)> {
+    let gcd = m.extended_gcd(&n);
+    if (b - a) % gcd.gcd != 0 {
+        return None;
+    }
+
+    let lcm = m / gcd.gcd * n;
+    let k = ((b - a) / gcd.gcd * gcd.x).rem_euclid(n / gcd.gcd);
+
+    Some(((a + m * k).rem_euclid(lcm), lcm))
+}
+
+/// First step where every ghost is on a Z node at the same time.
+fn first_common_hit(ghosts: &[Ghost]) -> Option<i64> {
+    // Either it's a step where some ghost hasn't started looping yet...
+    let before_loops = ghosts
+        .iter()
+        .flat_map(|ghost| &ghost.prefix_hits)
+        .copied()
+        .filter(|&step| ghosts.iter().all(|ghost| ghost.is_at_z(step)))
+        .min();
+
+    if before_loops.is_some() {
+        return before_loops;
+    }
+
+    // ...or every ghost is in its loop, and the step has to be congruent to one of the hits of
+    // each of them. Every combination of hits gets solved with the CRT.
+    let lower_bound = ghosts.iter().map(|ghost| ghost.cycle.start as i128).max()?;
+
+    let mut congruences = vec![(0, 1)];
+    for ghost in ghosts {
+        congruences = congruences
+            .iter()
+            .flat_map(|&congruence| {
+                ghost
+                    .cycle_hits
+                    .iter()
+                    .filter_map(move |&hit| crt(congruence, (hit as i128, ghost.period() as i128)))
+            })
+            .collect();
+    }
+
+    congruences
+        .into_iter()
+        .map(|(remainder, modulo)| {
+            let below = (lower_bound - remainder).max(0);
+            remainder + Integer::div_ceil(&below, &modulo) * modulo
+        })
+        .min()
+        .map(|step| step as i64)
+}
+
+fn ghosts<'a>(instructions: &[u8], edges: &Map<'a>) -> Vec<Ghost<'a>> {
+    let mut starts = edges
+        .keys()
+        .filter(|node| ends_with(node, b'A'))
+        .collect::<Vec<_>>();
+    starts.sort();
+
+    starts
+        .into_iter()
+        .map(|start| Ghost::new(start, instructions, edges))
+        .collect()
+}
+
 fn part2(input: &str) -> i64 {
     let (instructions, edges) = parser::whole(input).unwrap();
+    let ghosts = ghosts(&instructions, &edges);
+
+    for ghost in &ghosts {
+        if !ghost.is_multiples_of_first_hit() {
+            tracing::warn!(ghost.start, "The LCM shortcut doesn't work for this ghost");
+        }
+    }
 
-    let ends_with = |node: &str, char: u8| node.as_bytes()[2] == char;
+    first_common_hit(&ghosts).expect("The ghosts are never all on Z nodes at the same time")
+}
+
+#[cfg(test)]
+fn part2_lcm(input: &str) -> i64 {
+    let (instructions, edges) = parser::whole(input).unwrap();
 
     let starts = edges.keys().filter(|node| ends_with(node, b'A'));
 
@@ -73,6 +229,34 @@ fn part2(input: &str) -> i64 {
         .fold(1, num::integer::lcm)
 }
 
+fn explain(input: &str) {
+    let (instructions, edges) = parser::whole(input).unwrap();
+    let ghosts = ghosts(&instructions, &edges);
+
+    for ghost in &ghosts {
+        println!(
+            "{}: loops every {} steps after {} steps, Z at {:?} and then {:?} + k * {}",
+            ghost.start,
+            ghost.cycle.period,
+            ghost.cycle.start,
+            ghost.prefix_hits,
+            ghost.cycle_hits,
+            ghost.cycle.period
+        );
+    }
+
+    if ghosts.iter().all(Ghost::is_multiples_of_first_hit) {
+        println!("\nEvery ghost is on Z exactly at multiples of its first hit, so LCM works");
+    } else {
+        println!("\nSome ghosts aren't on Z exactly at multiples of their first hit, LCM is wrong");
+    }
+
+    match first_common_hit(&ghosts) {
+        Some(step) => println!("All ghosts are on Z nodes after {step} steps"),
+        None => println!("The ghosts are never all on Z nodes at the same time"),
+    }
+}
+
 christmas_tree::examples! {
     r"
         LLR
@@ -95,3 +279,45 @@ christmas_tree::examples! {
         XXX = (XXX, XXX)
     " => 6,
 }
+
+#[test]
+fn cycles_without_lcm() {
+    // Z at 1, 3, 5... and at 2, 4, 6...
+    let example = christmas_tree::indoc! {"
+        L
+
+        11A = (11Z, XXX)
+        11Z = (11B, XXX)
+        11B = (11Z, XXX)
+        22A = (22B, XXX)
+        22B = (22Z, XXX)
+        22Z = (22B, XXX)
+        XXX = (XXX, XXX)
+    "};
+
+    let (instructions, edges) = parser::whole(example).unwrap();
+    let ghosts = ghosts(&instructions, &edges);
+
+    assert!(!ghosts[0].is_multiples_of_first_hit());
+    assert!(ghosts[1].is_multiples_of_first_hit());
+    assert_eq!(first_common_hit(&ghosts), None);
+
+    // Z at 3, 5, 7... and at 2, 5, 8...
+    let example = christmas_tree::indoc! {"
+        L
+
+        11A = (11B, XXX)
+        11B = (11C, XXX)
+        11C = (11Z, XXX)
+        11Z = (11C, XXX)
+        22A = (22B, XXX)
+        22B = (22Z, XXX)
+        22Z = (22C, XXX)
+        22C = (22D, XXX)
+        22D = (22Z, XXX)
+        XXX = (XXX, XXX)
+    "};
+
+    assert_eq!(part2_lcm(example), 6);
+    assert_eq!(part2(example), 5);
+}
@@ -1,6 +1,19 @@
-christmas_tree::day!(9);
+christmas_tree::day!(9, explain);
 
-fn predict(values: &[i64], fold: &impl Fn(&[i64], i64) -> i64) -> i64 {
+use christmas_tree::polynomial::{self, Polynomial};
+
+fn parse(input: &str) -> impl Iterator<Item = Vec<i64>> + '_ {
+    input.lines().map(|line| {
+        line.split_whitespace()
+            .map(|value| value.parse().unwrap())
+            .collect()
+    })
+}
+
+/// The original solution: extends every row of differences by one value. Unlike
+/// [`polynomial::extrapolate`], it still gives an answer when the differences run out before
+/// reaching zeros.
+fn predict(values: &[i64], forward: bool) -> i64 {
     if values.iter().all(|v| *v == 0) {
         return 0;
     }
@@ -9,30 +22,44 @@ fn predict(values: &[i64], fold: &impl Fn(&[i64], i64) -> i64) -> i64 {
         .windows(2)
         .map(|window| window[1] - window[0])
         .collect::<Vec<_>>();
-    
-    fold(values, predict(&deltas, fold))
+
+    let delta = predict(&deltas, forward);
+    if forward {
+        values.last().unwrap() + delta
+    } else {
+        values[0] - delta
+    }
 }
 
-fn solve(input: &str, fold: &impl Fn(&[i64], i64) -> i64) -> i64 {
-    input
-        .lines()
-        .map(|line| {
-            let row = line
-                .split_whitespace()
-                .map(|value| value.parse().unwrap())
-                .collect::<Vec<_>>();
+fn solve(input: &str, forward: bool) -> i64 {
+    let steps = if forward { 1 } else { -1 };
 
-            predict(&row, fold)
-        })
+    parse(input)
+        .map(|row| polynomial::extrapolate(&row, steps).unwrap_or_else(|| predict(&row, forward)))
         .sum()
 }
 
 fn part1(input: &str) -> i64 {
-    solve(input, &|values, delta| values.last().unwrap() + delta)
+    solve(input, true)
 }
 
 fn part2(input: &str) -> i64 {
-    solve(input, &|values, delta| values[0] - delta)
+    solve(input, false)
+}
+
+fn explain(input: &str) {
+    for row in parse(input) {
+        let row_string = row.iter().map(i64::to_string).collect::<Vec<_>>().join(" ");
+
+        match Polynomial::fit(&row) {
+            Some(polynomial) => println!("{row_string}: {polynomial}"),
+            None if polynomial::differences(&row).is_none() => println!(
+                "{row_string}: not enough values to be sure it's a polynomial, \
+                 extrapolated with the differences"
+            ),
+            None => println!("{row_string}: polynomial too big for exact rationals"),
+        }
+    }
 }
 
 christmas_tree::examples! {
@@ -42,3 +69,10 @@ christmas_tree::examples! {
         10 13 16 21 30 45
     " => 114, 2,
 }
+
+#[test]
+fn differences_that_never_reach_zero() {
+    // Differences are [-7], and then nothing
+    assert_eq!(part1("5 -2"), -9);
+    assert_eq!(part2("5 -2"), 12);
+}
@@ -1,7 +1,14 @@
-christmas_tree::day!(10);
+christmas_tree::day!(10, visualize, render, explain);
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
+use christmas_tree::{
+    render::Image,
+    viz::{Color, Grid},
+};
 use glam::IVec2;
 
 #[allow(unused)]
@@ -16,6 +23,7 @@ enum Tile {
 enum NonTile {
     Empty,
     Start,
+    Unknown(char),
 }
 
 pub const NORTH: IVec2 = IVec2::new(0, -1);
@@ -50,16 +58,71 @@ impl TryFrom<char> for Tile {
             },
             '.' => return Err(NonTile::Empty),
             'S' => return Err(NonTile::Start),
-            _ => panic!("Invalid tile: {}", c),
+            other => return Err(NonTile::Unknown(other)),
         })
     }
 }
 
 impl Tile {
+    pub const ALL: [Tile; 6] = [
+        Tile::Pipe { vertical: true },
+        Tile::Pipe { vertical: false },
+        Tile::Bend {
+            north: true,
+            east: true,
+        },
+        Tile::Bend {
+            north: true,
+            east: false,
+        },
+        Tile::Bend {
+            north: false,
+            east: false,
+        },
+        Tile::Bend {
+            north: false,
+            east: true,
+        },
+    ];
+
     pub const fn all_neighbor_deltas() -> [IVec2; 4] {
         [NORTH, SOUTH, EAST, WEST]
     }
 
+    /// The tile that connects in exactly these two directions.
+    pub fn from_connections(a: IVec2, b: IVec2) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|tile| tile.connects(a) && tile.connects(b) && a != b)
+    }
+
+    pub const fn box_char(&self) -> char {
+        match self {
+            Tile::Pipe { vertical: true } => '│',
+            Tile::Pipe { vertical: false } => '─',
+            Tile::Bend {
+                north: true,
+                east: true,
+            } => '└',
+            Tile::Bend {
+                north: true,
+                east: false,
+            } => '┘',
+            Tile::Bend {
+                north: false,
+                east: false,
+            } => '┐',
+            Tile::Bend {
+                north: false,
+                east: true,
+            } => '┌',
+        }
+    }
+
+    pub fn connects(&self, delta: IVec2) -> bool {
+        self.neighbor_deltas().contains(&delta)
+    }
+
     pub fn neighbor_deltas(&self) -> [IVec2; 2] {
         match self {
             Self::Pipe { vertical: true } => [NORTH, SOUTH],
@@ -113,9 +176,65 @@ impl Tile {
     }
 }
 
-fn parse(input: &str) -> (HashMap<IVec2, Tile>, IVec2) {
+/// Something wrong with a map, found by [`validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Problem {
+    UnknownTile {
+        position: IVec2,
+        char: char,
+    },
+    MissingStart,
+    MultipleStarts(Vec<IVec2>),
+    /// The start should connect to exactly two pipes. If more do, the answers use the pair that
+    /// closes a loop (if there's one).
+    AmbiguousStart {
+        connections: Vec<IVec2>,
+    },
+    /// Pipes that lead to tiles that don't lead back to them, as their position and the direction
+    /// of the loose end. Grouped since junk pipes in real inputs have thousands of them.
+    DanglingPipes(Vec<(IVec2, IVec2)>),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownTile { position, char } => {
+                write!(f, "Unknown tile {char:?} at {position}")
+            }
+            Self::MissingStart => write!(f, "There is no start tile"),
+            Self::MultipleStarts(starts) => write!(f, "There are {} start tiles", starts.len()),
+            Self::AmbiguousStart { connections } => write!(
+                f,
+                "The start connects to {} pipes instead of 2",
+                connections.len()
+            ),
+            Self::DanglingPipes(ends) => match ends[..] {
+                [(position, towards)] => write!(
+                    f,
+                    "The pipe at {position} leads to {}, which doesn't connect back",
+                    position + towards
+                ),
+                [(position, towards), ..] => write!(
+                    f,
+                    "{} pipe ends lead to tiles that don't connect back, like the one at \
+                     {position} leading to {}",
+                    ends.len(),
+                    position + towards
+                ),
+                [] => Ok(()),
+            },
+        }
+    }
+}
+
+impl std::error::Error for Problem {}
+
+/// Reads every tile without checking whether the map makes sense. Returns the positions of all
+/// the starts, and the unknown tiles as problems.
+fn read_tiles(input: &str) -> (HashMap<IVec2, Tile>, Vec<IVec2>, Vec<Problem>) {
     let mut output = HashMap::new();
-    let mut start = None;
+    let mut starts = Vec::new();
+    let mut problems = Vec::new();
 
     for (y, line) in input.lines().enumerate() {
         for (x, c) in line.chars().enumerate() {
@@ -125,63 +244,196 @@ fn parse(input: &str) -> (HashMap<IVec2, Tile>, IVec2) {
                     output.insert(pos, tile);
                 }
 
-                Err(NonTile::Start) => {
-                    assert!(start.is_none());
-                    start = Some(pos);
-                }
+                Err(NonTile::Start) => starts.push(pos),
+
+                Err(NonTile::Unknown(char)) => problems.push(Problem::UnknownTile {
+                    position: pos,
+                    char,
+                }),
 
                 Err(NonTile::Empty) => (),
             };
         }
     }
 
-    let start = start.unwrap();
+    (output, starts, problems)
+}
 
-    set_start(&mut output, start);
+fn parse(input: &str) -> (HashMap<IVec2, Tile>, IVec2) {
+    let (mut output, starts, problems) = read_tiles(input);
+
+    if let Some(problem) = problems.first() {
+        panic!("{problem}");
+    }
+
+    let start = match starts[..] {
+        [start] => start,
+        [] => panic!("{}", Problem::MissingStart),
+        _ => panic!("{}", Problem::MultipleStarts(starts)),
+    };
+
+    set_start(&mut output, start).unwrap_or_else(|problem| panic!("{problem}"));
 
     (output, start)
 }
 
-fn set_start(map: &mut HashMap<IVec2, Tile>, start: IVec2) {
-    let start_is_north = map
-        .get(&(start + NORTH))
-        .map(|tile| tile.south())
-        .unwrap_or(false);
+/// Neighbors of the start that have a pipe connecting to it.
+fn start_connections(map: &HashMap<IVec2, Tile>, start: IVec2) -> Vec<IVec2> {
+    Tile::all_neighbor_deltas()
+        .into_iter()
+        .filter(|&delta| {
+            map.get(&(start + delta))
+                .is_some_and(|tile| tile.connects(-delta))
+        })
+        .collect()
+}
 
-    let start_is_east = map
-        .get(&(start + EAST))
-        .map(|tile| tile.west())
-        .unwrap_or(false);
+/// Replaces the start with the pipe that connects to its neighbors. If more than two of them
+/// connect to it, the pipe is the one that closes a loop.
+fn set_start(map: &mut HashMap<IVec2, Tile>, start: IVec2) -> Result<(), Problem> {
+    let connections = start_connections(map, start);
 
-    map.insert(
-        start,
-        Tile::Bend {
-            north: start_is_north,
-            east: start_is_east,
-        },
-    );
+    if let [a, b] = connections[..] {
+        map.insert(start, Tile::from_connections(a, b).unwrap());
+        return Ok(());
+    }
+
+    for (i, &a) in connections.iter().enumerate() {
+        for &b in &connections[i + 1..] {
+            map.insert(start, Tile::from_connections(a, b).unwrap());
+
+            if walk(map, start, &mut HashSet::new()).is_some() {
+                return Ok(());
+            }
+        }
+    }
+
+    map.remove(&start);
+    Err(Problem::AmbiguousStart { connections })
 }
 
-fn get_loop(map: &HashMap<IVec2, Tile>, start: IVec2) -> HashMap<IVec2, Tile> {
-    let mut output = HashMap::new();
+/// Follows the pipes from `start`, marking every tile as visited. Returns the path if it comes
+/// back to `start`, and `None` if it leads nowhere or into tiles that were already visited.
+fn walk(
+    map: &HashMap<IVec2, Tile>,
+    start: IVec2,
+    visited: &mut HashSet<IVec2>,
+) -> Option<Vec<IVec2>> {
+    let mut path = vec![start];
+    let mut previous = None;
+    let mut current = start;
+    visited.insert(start);
+
+    loop {
+        // Keep going in the direction that isn't where we came from
+        let [a, b] = map[&current].neighbor_deltas();
+        let delta = if previous == Some(current + a) { b } else { a };
+        let next = current + delta;
+
+        let connected = map.get(&next).is_some_and(|tile| tile.connects(-delta));
+        if !connected {
+            return None;
+        }
+
+        if next == start {
+            return Some(path);
+        }
+
+        // A loop is either walked completely or not at all, so this one is open
+        if !visited.insert(next) {
+            return None;
+        }
+
+        path.push(next);
+        previous = Some(current);
+        current = next;
+    }
+}
+
+/// Every closed loop in the map, walked from its top left tile. Chains of pipes that lead nowhere
+/// are skipped.
+fn find_loops(map: &HashMap<IVec2, Tile>) -> Vec<Vec<IVec2>> {
+    let mut positions = map.keys().copied().collect::<Vec<_>>();
+    positions.sort_by_key(|pos| (pos.y, pos.x));
+
+    let mut visited = HashSet::new();
+    let mut output = Vec::new();
+
+    for start in positions {
+        if visited.contains(&start) {
+            continue;
+        }
+
+        output.extend(walk(map, start, &mut visited));
+    }
+
+    output
+}
+
+/// Everything wrong with the map, and every closed loop in it (the start is replaced by its
+/// pipe if that's possible).
+fn validate(input: &str) -> (Vec<Problem>, Vec<Vec<IVec2>>) {
+    let (mut map, starts, mut problems) = read_tiles(input);
+
+    match starts[..] {
+        [start] => {
+            let connections = start_connections(&map, start);
+
+            if let Err(problem) = set_start(&mut map, start) {
+                problems.push(problem);
+            } else if connections.len() != 2 {
+                problems.push(Problem::AmbiguousStart { connections });
+            }
+        }
+        [] => problems.push(Problem::MissingStart),
+        _ => problems.push(Problem::MultipleStarts(starts)),
+    }
+
+    let mut positions = map.keys().copied().collect::<Vec<_>>();
+    positions.sort_by_key(|pos| (pos.y, pos.x));
+
+    let mut dangling = Vec::new();
+    for position in positions {
+        for towards in map[&position].neighbor_deltas() {
+            let connected = map
+                .get(&(position + towards))
+                .is_some_and(|tile| tile.connects(-towards));
+
+            if !connected {
+                dangling.push((position, towards));
+            }
+        }
+    }
+
+    if !dangling.is_empty() {
+        problems.push(Problem::DanglingPipes(dangling));
+    }
+
+    (problems, find_loops(&map))
+}
+
+/// Positions of the loop in the order they are walked, starting from `start`.
+fn get_loop_path(map: &HashMap<IVec2, Tile>, start: IVec2) -> Vec<IVec2> {
+    let mut output = vec![start];
+    let mut visited = HashSet::from([start]);
 
     let mut pos = start;
 
     loop {
         let tile = map.get(&pos).unwrap();
 
-        output.insert(pos, *tile);
-
         let mut found = false;
 
         for delta in tile.neighbor_deltas() {
-            if output.contains_key(&(pos + delta)) {
+            let neighbor = pos + delta;
+
+            if visited.contains(&neighbor) {
                 continue;
             }
 
-            let neighbor = pos + delta;
-
             if map.contains_key(&neighbor) {
+                output.push(neighbor);
+                visited.insert(neighbor);
                 pos = neighbor;
                 found = true;
                 break;
@@ -196,6 +448,13 @@ fn get_loop(map: &HashMap<IVec2, Tile>, start: IVec2) -> HashMap<IVec2, Tile> {
     output
 }
 
+fn get_loop(map: &HashMap<IVec2, Tile>, start: IVec2) -> HashMap<IVec2, Tile> {
+    get_loop_path(map, start)
+        .into_iter()
+        .map(|pos| (pos, map[&pos]))
+        .collect()
+}
+
 fn part1(input: &str) -> i64 {
     let (map, start) = parse(input);
 
@@ -204,17 +463,14 @@ fn part1(input: &str) -> i64 {
     main_loop.len() as i64 / 2
 }
 
-fn part2(input: &str) -> i64 {
-    let (map, start) = parse(input);
-
+/// Tiles enclosed by the loop, found by casting a ray from the west edge of each row.
+fn enclosed_tiles(main_loop: &HashMap<IVec2, Tile>) -> Vec<IVec2> {
     let size = IVec2::new(
-        map.keys().map(|pos| pos.x).max().unwrap(),
-        map.keys().map(|pos| pos.y).max().unwrap(),
+        main_loop.keys().map(|pos| pos.x).max().unwrap(),
+        main_loop.keys().map(|pos| pos.y).max().unwrap(),
     );
 
-    let main_loop = get_loop(&map, start);
-
-    let mut count = 0;
+    let mut output = Vec::new();
 
     for y in 0..=size.y {
         let mut inside = false;
@@ -228,7 +484,7 @@ fn part2(input: &str) -> i64 {
                 }
 
                 None if inside => {
-                    count += 1;
+                    output.push(pos);
                 }
 
                 _ => (),
@@ -236,86 +492,320 @@ fn part2(input: &str) -> i64 {
         }
     }
 
-    count
+    output
+}
+
+fn part2(input: &str) -> i64 {
+    let (map, start) = parse(input);
+
+    let main_loop = get_loop(&map, start);
+
+    enclosed_tiles(&main_loop).len() as i64
 }
 
-christmas_tree::examples! {
-    part1 {
-        simple: r"
+/// Counts the enclosed tiles treating the loop as a polygon through the centers of its tiles.
+/// The shoelace formula gives its area, and Pick's theorem (`A = i + b/2 - 1`) turns that into
+/// the amount of points strictly inside it, with every tile of the loop being a boundary point.
+fn enclosed_count_pick(path: &[IVec2]) -> i64 {
+    // Only the bends change direction, so they are the only vertices that matter
+    let vertices = path.iter().enumerate().filter(|&(i, pos)| {
+        let previous = path[(i + path.len() - 1) % path.len()];
+        let next = path[(i + 1) % path.len()];
+        next - *pos != *pos - previous
+    });
+    let vertices = vertices
+        .map(|(_, pos)| pos.as_i64vec2())
+        .collect::<Vec<_>>();
+
+    let double_area = (0..vertices.len())
+        .map(|i| {
+            let a = vertices[i];
+            let b = vertices[(i + 1) % vertices.len()];
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<i64>()
+        .abs();
+
+    let boundary = path.len() as i64;
+
+    (double_area - boundary) / 2 + 1
+}
+
+#[cfg(test)]
+fn part2_pick(input: &str) -> i64 {
+    let (map, start) = parse(input);
+
+    enclosed_count_pick(&get_loop_path(&map, start))
+}
+
+fn explain(input: &str) {
+    let (problems, loops) = validate(input);
+
+    for problem in &problems {
+        println!("{problem}");
+    }
+
+    if problems.is_empty() {
+        println!("The map has no problems");
+    }
+
+    let start = read_tiles(input).1.first().copied();
+
+    println!();
+    for path in &loops {
+        println!(
+            "Loop from {}{} is {} tiles long and encloses {} tiles",
+            path[0],
+            if start.is_some_and(|start| path.contains(&start)) {
+                " (through the start)"
+            } else {
+                ""
+            },
+            path.len(),
+            enclosed_count_pick(path)
+        );
+    }
+}
+
+/// The map with the main loop drawn with box-drawing characters, the tiles it encloses
+/// highlighted and everything outside of it dimmed.
+fn draw(input: &str) -> Grid {
+    let (map, start) = parse(input);
+    let main_loop = get_loop(&map, start);
+    let enclosed = enclosed_tiles(&main_loop);
+
+    let mut grid = Grid::from_text(input);
+
+    for (pos, tile) in &main_loop {
+        grid.set(pos.as_i64vec2(), tile.box_char());
+    }
+
+    let inside = main_loop
+        .keys()
+        .chain(&enclosed)
+        .map(|pos| pos.as_i64vec2())
+        .collect::<HashSet<_>>();
+
+    let outside = grid
+        .cells()
+        .map(|(pos, _)| pos)
+        .filter(|pos| !inside.contains(&glam::I64Vec2::from(*pos)))
+        .collect::<Vec<_>>();
+
+    grid.highlight(main_loop.keys().map(|pos| pos.as_i64vec2()), Color::Yellow)
+        .highlight(enclosed.iter().map(|pos| pos.as_i64vec2()), Color::Black)
+        .region(enclosed.iter().map(|pos| pos.as_i64vec2()), Color::Green)
+        .dim(outside)
+        .highlight([start.as_i64vec2()], Color::Red);
+
+    grid
+}
+
+fn visualize(input: &str) {
+    draw(input).print();
+}
+
+fn render(input: &str) -> Image {
+    Image::Grid(draw(input))
+}
+
+#[cfg(test)]
+const LOOP_SIMPLE: &str = christmas_tree::indoc! {"
+    .....
+    .S-7.
+    .|.|.
+    .L-J.
+    .....
+"};
+
+#[cfg(test)]
+const LOOP_SIMPLE_WITH_PIPES: &str = christmas_tree::indoc! {"
+    -L|F7
+    7S-7|
+    L|7||
+    -L-J|
+    L|-JF
+"};
+
+#[cfg(test)]
+const LOOP_COMPLEX: &str = christmas_tree::indoc! {"
+    ..F7.
+    .FJ|.
+    SJ.L7
+    |F--J
+    LJ...
+"};
+
+#[cfg(test)]
+const ENCLOSED_SIMPLE: &str = christmas_tree::indoc! {"
+    ...........
+    .S-------7.
+    .|F-----7|.
+    .||.....||.
+    .||.....||.
+    .|L-7.F-J|.
+    .|..|.|..|.
+    .L--J.L--J.
+    ...........
+"};
+
+#[cfg(test)]
+const ENCLOSED_SQEEZE: &str = christmas_tree::indoc! {"
+    ..........
+    .S------7.
+    .|F----7|.
+    .||....||.
+    .||....||.
+    .|L-7F-J|.
+    .|..||..|.
+    .L--JL--J.
+    ..........
+"};
+
+#[cfg(test)]
+const ENCLOSED_LARGER: &str = christmas_tree::indoc! {"
+    .F----7F7F7F7F-7....
+    .|F--7||||||||FJ....
+    .||.FJ||||||||L7....
+    FJL7L7LJLJ||LJ.L-7..
+    L--J.L7...LJS7F-7L7.
+    ....F-J..F7FJ|L7L7L7
+    ....L7.F7||L7|.L7L7|
+    .....|FJLJ|FJ|F7|.LJ
+    ....FJL-7.||.||||...
+    ....L---J.LJ.LJLJ...
+"};
+
+#[cfg(test)]
+const ENCLOSED_LARGEST: &str = christmas_tree::indoc! {"
+    FF7FSF7F7F7F7F7F---7
+    L|LJ||||||||||||F--J
+    FL-7LJLJ||||||LJL-77
+    F--JF--7||LJLJ7F7FJ-
+    L---JF-JLJ.||-FJLJJ7
+    |F|F-JF---7F7-L7L|7|
+    |FFJF7L7F-JF7|JL---7
+    7-L-JL7||F7|L7F-7F7|
+    L.L7LFJ|||||FJL7||LJ
+    L7JLJL-JLJLJL--JLJ.L
+"};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1() {
+        for (example, expected) in [
+            (LOOP_SIMPLE, 4),
+            (LOOP_SIMPLE_WITH_PIPES, 4),
+            (LOOP_COMPLEX, 8),
+        ] {
+            assert_eq!(super::part1(example), expected, "{example}");
+        }
+    }
+
+    #[test]
+    fn part2() {
+        for (example, expected) in [
+            (ENCLOSED_SIMPLE, 4),
+            (ENCLOSED_SQEEZE, 4),
+            (ENCLOSED_LARGER, 8),
+            (ENCLOSED_LARGEST, 10),
+        ] {
+            assert_eq!(super::part2(example), expected, "{example}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod enclosure {
+    use super::{
+        ENCLOSED_LARGER, ENCLOSED_LARGEST, ENCLOSED_SIMPLE, ENCLOSED_SQEEZE, LOOP_COMPLEX,
+        LOOP_SIMPLE, LOOP_SIMPLE_WITH_PIPES,
+    };
+
+    #[test]
+    fn validate() {
+        use super::{IVec2, Problem, Tile};
+
+        let (problems, loops) = super::validate(LOOP_SIMPLE_WITH_PIPES);
+        assert_eq!(loops.len(), 1);
+        assert!(matches!(problems[..], [Problem::DanglingPipes(_)]));
+
+        // The start connects to three pipes, but only two of them close a loop
+        let three_connections = christmas_tree::indoc! {"
             .....
-            .S-7.
+            -S-7.
             .|.|.
             .L-J.
-            .....
-        " => 4,
-
-        simple_with_pipes: r"
-            -L|F7
-            7S-7|
-            L|7||
-            -L-J|
-            L|-JF
-        " => 4,
-
-        complex: r"
-            ..F7.
-            .FJ|.
-            SJ.L7
-            |F--J
-            LJ...
-        " => 8,
-
+        "};
+        assert_eq!(super::part1(three_connections), 4);
+        assert_eq!(super::part2(three_connections), 1);
+
+        let (problems, loops) = super::validate(three_connections);
+        assert_eq!(loops.len(), 1);
+        assert!(matches!(
+            &problems[..],
+            [Problem::AmbiguousStart { connections }, Problem::DanglingPipes(ends)]
+                if connections.len() == 3 && ends.len() == 2
+        ));
+
+        let two_loops = christmas_tree::indoc! {"
+            S-7.F7
+            |.|.LJ
+            L-J...
+        "};
+        let (problems, loops) = super::validate(two_loops);
+        assert_eq!(problems, []);
+        assert_eq!(
+            loops.iter().map(|path| path.len()).collect::<Vec<_>>(),
+            [8, 4]
+        );
+        assert_eq!(
+            loops
+                .iter()
+                .map(|path| super::enclosed_count_pick(path))
+                .collect::<Vec<_>>(),
+            [1, 0]
+        );
+
+        let ambiguous = christmas_tree::indoc! {"
+            .|.
+            -S-
+            .|?
+        "};
+        let (problems, loops) = super::validate(ambiguous);
+        assert_eq!(loops, Vec::<Vec<IVec2>>::new());
+        assert_eq!(
+            problems[..2],
+            [
+                Problem::UnknownTile {
+                    position: IVec2::new(2, 2),
+                    char: '?'
+                },
+                Problem::AmbiguousStart {
+                    connections: Tile::all_neighbor_deltas().to_vec()
+                },
+            ]
+        );
     }
 
-    part2 {
-        simple: r"
-            ...........
-            .S-------7.
-            .|F-----7|.
-            .||.....||.
-            .||.....||.
-            .|L-7.F-J|.
-            .|..|.|..|.
-            .L--J.L--J.
-            ...........
-        " => 4,
-
-        sqeeze: r"
-            ..........
-            .S------7.
-            .|F----7|.
-            .||....||.
-            .||....||.
-            .|L-7F-J|.
-            .|..||..|.
-            .L--JL--J.
-            ..........
-        " => 4,
-
-        larger: r"
-            .F----7F7F7F7F-7....
-            .|F--7||||||||FJ....
-            .||.FJ||||||||L7....
-            FJL7L7LJLJ||LJ.L-7..
-            L--J.L7...LJS7F-7L7.
-            ....F-J..F7FJ|L7L7L7
-            ....L7.F7||L7|.L7L7|
-            .....|FJLJ|FJ|F7|.LJ
-            ....FJL-7.||.||||...
-            ....L---J.LJ.LJLJ...
-        " => 8,
-
-        largest: r"
-            FF7FSF7F7F7F7F7F---7
-            L|LJ||||||||||||F--J
-            FL-7LJLJ||||||LJL-77
-            F--JF--7||LJLJ7F7FJ-
-            L---JF-JLJ.||-FJLJJ7
-            |F|F-JF---7F7-L7L|7|
-            |FFJF7L7F-JF7|JL---7
-            7-L-JL7||F7|L7F-7F7|
-            L.L7LFJ|||||FJL7||LJ
-            L7JLJL-JLJLJL--JLJ.L
-        " => 10,
+    #[test]
+    fn enclosure_methods_agree() {
+        for example in [
+            LOOP_SIMPLE,
+            LOOP_SIMPLE_WITH_PIPES,
+            LOOP_COMPLEX,
+            ENCLOSED_SIMPLE,
+            ENCLOSED_SQEEZE,
+            ENCLOSED_LARGER,
+            ENCLOSED_LARGEST,
+        ] {
+            let ray_casting = super::part2(example);
+
+            assert_eq!(super::original::part2(example), ray_casting, "{example}");
+            assert_eq!(super::part2_pick(example), ray_casting, "{example}");
+        }
     }
 }
@@ -33,7 +33,8 @@ fn parse_part2(input: &str) -> (HashMap<IVec2, Tile>, IVec2, IVec2) {
     let size = size * 2;
 
     let start = start.unwrap();
-    set_start(&mut output, start);
+    let (original_map, original_start) = parse(input);
+    output.insert(start, original_map[&original_start]);
 
     for y in 0..=size.y {
         for x in 0..=size.x {
@@ -1,11 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{BinaryHeap, HashSet};
 
-christmas_tree::day!(11);
+christmas_tree::day!(11, explain);
 
 type Vec2 = glam::I64Vec2;
 
-fn parse(input: &str, expansion_multiplier: i64) -> Vec<Vec2> {
-    let expansion_size = expansion_multiplier - 1;
+/// Galaxies after expanding the universe. Every empty column becomes `expansion.x` columns, and
+/// every empty row `expansion.y` rows.
+fn parse(input: &str, expansion: Vec2) -> Vec<Vec2> {
+    let expansion_size = expansion - 1;
 
     let size = Vec2::new(
         input.lines().next().unwrap().len() as i64,
@@ -28,7 +30,7 @@ fn parse(input: &str, expansion_multiplier: i64) -> Vec<Vec2> {
 
         for x in 0..size.x {
             if empty_columnns.contains(&x) {
-                offset.x += expansion_size;
+                offset.x += expansion_size.x;
             } else if index(x, y) == b'#' {
                 output.push(Vec2::new(x, y) + offset);
                 is_column_empty = false;
@@ -36,26 +38,102 @@ fn parse(input: &str, expansion_multiplier: i64) -> Vec<Vec2> {
         }
 
         if is_column_empty {
-            offset.y += expansion_size;
+            offset.y += expansion_size.y;
         }
     }
 
     output
 }
 
-fn solve(input: &str, expansion_multiplier: i64) -> i64 {
-    let galaxies = parse(input, expansion_multiplier);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    /// Moving only horizontally or vertically
+    Manhattan,
+    /// Moving diagonally too
+    Chebyshev,
+}
 
-    let mut count = 0;
+impl Metric {
+    fn distance(&self, a: Vec2, b: Vec2) -> i64 {
+        let Vec2 { x, y } = (a - b).abs();
 
-    for (i, &galaxy) in galaxies.iter().enumerate() {
-        for &other in galaxies[i + 1..].iter() {
-            let Vec2 { x, y } = (galaxy - other).abs();
-            count += x + y;
+        match self {
+            Metric::Manhattan => x + y,
+            Metric::Chebyshev => x.max(y),
         }
     }
+}
+
+/// Sum of `|a - b|` over every pair of values. Once sorted, each value is bigger than all the ones
+/// before it, so it adds itself once for each of them and subtracts their sum.
+fn pair_difference_sum(mut values: Vec<i64>) -> i64 {
+    values.sort_unstable();
+
+    let mut prefix = 0;
+    let mut output = 0;
+
+    for (i, value) in values.into_iter().enumerate() {
+        output += value * i as i64 - prefix;
+        prefix += value;
+    }
 
-    count
+    output
+}
+
+/// Sum of the distances between every pair of galaxies, in O(n log n).
+fn pair_distance_sum(galaxies: &[Vec2], metric: Metric) -> i64 {
+    let axis = |f: fn(&Vec2) -> i64| pair_difference_sum(galaxies.iter().map(f).collect());
+
+    match metric {
+        Metric::Manhattan => axis(|g| g.x) + axis(|g| g.y),
+
+        // Rotating by 45° turns Chebyshev distances into half of Manhattan distances
+        Metric::Chebyshev => (axis(|g| g.x + g.y) + axis(|g| g.x - g.y)) / 2,
+    }
+}
+
+/// A pair of galaxies (by index) and the distance between them.
+type Pair = (i64, usize, usize);
+
+/// The `k` pairs of galaxies that are closest to each other, or the farthest apart, from the
+/// closest to the farthest. Only `k` pairs are kept around at a time, but it still looks at all of
+/// them.
+fn extreme_pairs(galaxies: &[Vec2], metric: Metric, k: usize, farthest: bool) -> Vec<Pair> {
+    // Keeps the pair that should be dropped first at the top
+    let mut heap = BinaryHeap::new();
+
+    for (i, &a) in galaxies.iter().enumerate() {
+        for (j, &b) in galaxies.iter().enumerate().skip(i + 1) {
+            let distance = metric.distance(a, b);
+            let key = if farthest { -distance } else { distance };
+
+            heap.push((key, i, j));
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+    }
+
+    let mut output = heap
+        .into_iter()
+        .map(|(key, i, j)| (if farthest { -key } else { key }, i, j))
+        .collect::<Vec<_>>();
+    output.sort();
+
+    output
+}
+
+fn distance_matrix(galaxies: &[Vec2], metric: Metric) -> Vec<Vec<i64>> {
+    galaxies
+        .iter()
+        .map(|&a| galaxies.iter().map(|&b| metric.distance(a, b)).collect())
+        .collect()
+}
+
+fn solve(input: &str, expansion_multiplier: i64) -> i64 {
+    let galaxies = parse(input, Vec2::splat(expansion_multiplier));
+
+    pair_distance_sum(&galaxies, Metric::Manhattan)
 }
 
 fn part1(input: &str) -> i64 {
@@ -66,17 +144,106 @@ fn part2(input: &str) -> i64 {
     solve(input, 1_000_000)
 }
 
-christmas_tree::examples! {
-    r"
-        ...#......
-        .......#..
-        #.........
-        ..........
-        ......#...
-        .#........
-        .........#
-        ..........
-        .......#..
-        #...#.....
-    " => 374, 82000210, // Got the second example myself, because given example has different parameters
+/// Expands like part 1 unless told otherwise with `--param columns=N` and `--param rows=N`.
+fn explain(input: &str) {
+    const PAIRS: usize = 5;
+    const MAX_MATRIX: usize = 20;
+
+    let expansion = Vec2::new(
+        christmas_tree::param("columns").unwrap_or(2),
+        christmas_tree::param("rows").unwrap_or(2),
+    );
+    let galaxies = parse(input, expansion);
+    let metric = Metric::Manhattan;
+
+    println!(
+        "{} galaxies after turning every empty column into {} and every empty row into {}",
+        galaxies.len(),
+        expansion.x,
+        expansion.y
+    );
+    println!(
+        "Distance sum: {} ({} moving diagonally too)\n",
+        pair_distance_sum(&galaxies, metric),
+        pair_distance_sum(&galaxies, Metric::Chebyshev)
+    );
+
+    for (name, farthest) in [("closest", false), ("farthest", true)] {
+        println!("The {PAIRS} {name} pairs:");
+        for (distance, i, j) in extreme_pairs(&galaxies, metric, PAIRS, farthest) {
+            println!("  {} and {}: {distance}", i + 1, j + 1);
+        }
+    }
+
+    if galaxies.len() <= MAX_MATRIX {
+        println!("\nDistances:");
+        for row in distance_matrix(&galaxies, metric) {
+            let row = row.iter().map(|d| format!("{d:>4}")).collect::<String>();
+            println!("{row}");
+        }
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = christmas_tree::indoc! {"
+    ...#......
+    .......#..
+    #.........
+    ..........
+    ......#...
+    .#........
+    .........#
+    ..........
+    .......#..
+    #...#.....
+"};
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn part1() {
+        assert_eq!(super::part1(super::EXAMPLE), 374);
+    }
+
+    #[test]
+    fn part2() {
+        // Got this one myself, because the given example has different parameters
+        assert_eq!(super::part2(super::EXAMPLE), 82000210);
+    }
+}
+
+#[test]
+fn pair_sum_matches_all_pairs() {
+    for expansion in [Vec2::new(2, 2), Vec2::new(10, 100), Vec2::new(1, 7)] {
+        let galaxies = parse(EXAMPLE, expansion);
+
+        for metric in [Metric::Manhattan, Metric::Chebyshev] {
+            let matrix = distance_matrix(&galaxies, metric);
+            let all_pairs = matrix.iter().flatten().sum::<i64>() / 2;
+
+            assert_eq!(pair_distance_sum(&galaxies, metric), all_pairs);
+        }
+    }
+
+    let galaxies = parse(EXAMPLE, Vec2::splat(2));
+
+    // From the puzzle: galaxies 5 and 9 are 9 apart, 1 and 7 are 15, 3 and 6 are 17
+    let matrix = distance_matrix(&galaxies, Metric::Manhattan);
+    assert_eq!([matrix[4][8], matrix[0][6], matrix[2][5]], [9, 15, 17]);
+
+    let mut distances = (0..galaxies.len())
+        .flat_map(|i| matrix[i][i + 1..].to_vec())
+        .collect::<Vec<_>>();
+    distances.sort();
+
+    let closest = extreme_pairs(&galaxies, Metric::Manhattan, 3, false);
+    let farthest = extreme_pairs(&galaxies, Metric::Manhattan, 2, true);
+    assert_eq!(
+        closest.iter().map(|p| p.0).collect::<Vec<_>>(),
+        distances[..3]
+    );
+    assert_eq!(
+        farthest.iter().map(|p| p.0).collect::<Vec<_>>(),
+        distances[distances.len() - 2..]
+    );
 }
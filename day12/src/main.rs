@@ -1,12 +1,19 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::{self, BufRead, Write},
+    ops::{Add, Mul},
+};
 
 use rayon::{prelude::ParallelIterator, str::ParallelString};
 
-christmas_tree::day!(12);
+christmas_tree::day!(12, explain, repl);
+
+mod nonogram;
 
 peg::parser! {
     grammar parser() for str {
-        rule number() -> i64
+        rule number() -> usize
             = n:$(['0'..='9']+) { n.parse().unwrap() }
 
         rule tile() -> Spring
@@ -16,10 +23,10 @@ peg::parser! {
 
         rule _ = [' ' | '\t' | '\n']*
 
-        pub rule line() -> (Vec<Spring>, Vec<i64>)
+        pub rule line() -> (Vec<Spring>, Vec<usize>)
             = tiles:tile()+ _ numbers:number() ** "," { (tiles, numbers) }
 
-        pub rule lines() -> Vec<(Vec<Spring>, Vec<i64>)>
+        pub rule lines() -> Vec<(Vec<Spring>, Vec<usize>)>
             = l:line() ** "\n" { l }
     }
 }
@@ -31,137 +38,604 @@ enum Spring {
     Unknown,
 }
 
-#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
-struct BundleData {
-    index: usize,
-    used: i64,
-    last_was_operational: bool,
+impl Spring {
+    fn can_be_operational(&self) -> bool {
+        *self != Spring::Damaged
+    }
+
+    fn can_be_damaged(&self) -> bool {
+        *self != Spring::Operational
+    }
 }
 
-#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
-struct Data {
-    spring_index: usize,
-    bundle: BundleData,
+/// Whether something is possible at all, for when the amount of ways doesn't matter (or is too
+/// big to count). Adding means "or".
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+struct Possible(bool);
+
+impl Add for Possible {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Possible(self.0 || other.0)
+    }
 }
 
-impl Data {
-    pub fn next(&self) -> Self {
-        Self {
-            spring_index: self.spring_index + 1,
-            ..self.clone()
-        }
+/// An amount of arrangements, or `None` if it got too big for a `u128` along the way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Count(Option<u128>);
+
+impl Default for Count {
+    fn default() -> Self {
+        Count(Some(0))
     }
+}
 
-    pub fn use_bundle(&self) -> Self {
-        Self {
-            spring_index: self.spring_index + 1,
-            bundle: BundleData {
-                used: self.bundle.used + 1,
-                last_was_operational: true,
-                ..self.bundle
-            },
-        }
+impl Add for Count {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Count(self.0.zip(other.0).and_then(|(a, b)| a.checked_add(b)))
     }
+}
 
-    pub fn next_bundle(&self) -> Self {
-        Self {
-            spring_index: self.spring_index + 1,
-            bundle: BundleData {
-                used: 0,
-                index: self.bundle.index + 1,
-                last_was_operational: false,
-            },
+impl Mul for Count {
+    type Output = Self;
+
+    /// Zero times anything is zero, even if the other count overflowed.
+    fn mul(self, other: Self) -> Self {
+        match (self.0, other.0) {
+            (Some(0), _) | (_, Some(0)) => Count(Some(0)),
+            (a, b) => Count(a.zip(b).and_then(|(a, b)| a.checked_mul(b))),
         }
     }
 }
 
-fn count_arrangments(springs: &[Spring], bundles: &[i64]) -> i64 {
-    fn execute(
-        data: Data,
-        springs: &[Spring],
-        bundles: &[i64],
-        cache: &mut HashMap<Data, i64>,
-    ) -> i64 {
-        if let Some(&count) = cache.get(&data) {
-            return count;
+/// Row-major table indexed by `(spring index, bundle index)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Table<T> {
+    bundles: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Copy> Table<T> {
+    fn get(&self, spring: usize, bundle: usize) -> T {
+        self.cells[spring * (self.bundles + 1) + bundle]
+    }
+}
+
+/// A row of springs and the sizes of the bundles of operational springs in it.
+#[derive(Clone, Copy, Debug)]
+struct Row<'a> {
+    springs: &'a [Spring],
+    bundles: &'a [usize],
+}
+
+impl<'a> Row<'a> {
+    fn new(springs: &'a [Spring], bundles: &'a [usize]) -> Self {
+        Self { springs, bundles }
+    }
+
+    /// Whether bundle `bundle` fits starting at `start`: all of its springs can be operational and
+    /// the one after it (if any) can be damaged. Returns where the rest of the row starts.
+    fn place(&self, start: usize, bundle: usize, runs: &[usize]) -> Option<usize> {
+        let end = start + self.bundles[bundle];
+        let fits = runs[start] >= self.bundles[bundle]
+            && self.springs.get(end).is_none_or(Spring::can_be_damaged);
+
+        fits.then_some((end + 1).min(self.springs.len()))
+    }
+
+    /// Amount of springs starting at each index that can be operational in a row.
+    fn runs(&self) -> Vec<usize> {
+        let mut runs = vec![0; self.springs.len() + 1];
+        for (i, spring) in self.springs.iter().enumerate().rev() {
+            if spring.can_be_operational() {
+                runs[i] = runs[i + 1] + 1;
+            }
         }
 
-        let Data {
-            spring_index,
-            bundle,
-        } = data;
+        runs
+    }
 
-        let spring = springs.get(spring_index);
-        let bundle_size = bundles
-            .get(bundle.index)
-            .map(|b| b - bundle.used)
-            .filter(|b| *b > 0);
+    /// Table where `(i, b)` is the amount of arrangements of the springs from `i` onwards with the
+    /// bundles from `b` onwards. `one` is what a single arrangement counts as.
+    fn suffix_table<T: Copy + Default + Add<Output = T>>(&self, one: T) -> Table<T> {
+        let springs = self.springs.len();
+        let bundles = self.bundles.len();
+        let runs = self.runs();
 
-        let exec_operational = |cache| match bundle_size {
-            None => 0,
-            Some(_) => execute(data.use_bundle(), springs, bundles, cache),
+        let mut table = Table {
+            bundles,
+            cells: vec![T::default(); (springs + 1) * (bundles + 1)],
         };
+        table.cells[springs * (bundles + 1) + bundles] = one;
+
+        for i in (0..springs).rev() {
+            for b in 0..=bundles {
+                let mut ways = T::default();
+
+                if self.springs[i].can_be_damaged() {
+                    ways = ways + table.get(i + 1, b);
+                }
 
-        let exec_damaged = |cache| match bundle_size {
-            None => execute(data.next_bundle(), springs, bundles, cache),
-            Some(_) => {
-                if data.bundle.last_was_operational {
-                    0
-                } else {
-                    execute(data.next(), springs, bundles, cache)
+                if b < bundles && self.springs[i].can_be_operational() {
+                    if let Some(next) = self.place(i, b, &runs) {
+                        ways = ways + table.get(next, b + 1);
+                    }
                 }
+
+                table.cells[i * (bundles + 1) + b] = ways;
             }
+        }
+
+        table
+    }
+
+    fn count(&self) -> Option<u128> {
+        self.suffix_table(Count(Some(1))).get(0, 0).0
+    }
+
+    /// Every arrangement of the row, one at a time. Branches without any arrangement are cut off
+    /// early, so each of them takes time proportional to the length of the row.
+    fn arrangements(&self) -> Arrangements<'a> {
+        let possible = self.suffix_table(Possible(true));
+        let stack = if possible.get(0, 0).0 {
+            vec![(0, 0, Vec::new())]
+        } else {
+            Vec::new()
         };
 
-        let result = match spring {
-            None => match bundle_size {
-                None if bundle.index >= bundles.len()
-                    || (bundle.index == bundles.len() - 1
-                        && bundles[bundle.index] == bundle.used) =>
-                {
-                    1
+        Arrangements {
+            row: *self,
+            runs: self.runs(),
+            possible,
+            stack,
+        }
+    }
+
+    /// Narrows down the unknown springs of the row to the ones that could go either way in a valid
+    /// arrangement. `None` if there's no valid arrangement at all.
+    fn deduce(&self) -> Option<Vec<Spring>> {
+        let springs = self.springs.len();
+        let bundles = self.bundles.len();
+        let runs = self.runs();
+
+        let suffix = self.suffix_table(Possible(true));
+
+        // Same thing backwards, so that `prefix(i, b)` tells if the springs before `i` can hold
+        // the bundles before `b`
+        let reversed_springs = self.springs.iter().rev().copied().collect::<Vec<_>>();
+        let reversed_bundles = self.bundles.iter().rev().copied().collect::<Vec<_>>();
+        let reversed = Row::new(&reversed_springs, &reversed_bundles).suffix_table(Possible(true));
+        let prefix = |i: usize, b: usize| reversed.get(springs - i, bundles - b).0;
+
+        if !suffix.get(0, 0).0 {
+            return None;
+        }
+
+        let mut can_be_damaged = vec![false; springs];
+        let mut operational_from = vec![0i32; springs + 1];
+
+        for i in 0..springs {
+            if self.springs[i].can_be_damaged() {
+                can_be_damaged[i] = (0..=bundles).any(|b| prefix(i, b) && suffix.get(i + 1, b).0);
+            }
+
+            for b in 0..bundles {
+                let before = match i {
+                    0 => b == 0,
+                    _ => self.springs[i - 1].can_be_damaged() && prefix(i - 1, b),
+                };
+
+                let after = || {
+                    self.place(i, b, &runs)
+                        .is_some_and(|next| suffix.get(next, b + 1).0)
+                };
+
+                if before && after() {
+                    operational_from[i] += 1;
+                    operational_from[i + self.bundles[b]] -= 1;
                 }
-                _ => 0,
-            },
-
-            Some(spring) => match spring {
-                Spring::Operational => exec_operational(cache),
-                Spring::Damaged => exec_damaged(cache),
-                Spring::Unknown => exec_operational(cache) + exec_damaged(cache),
-            },
+            }
+        }
+
+        let mut covering = 0;
+        let output = (0..springs)
+            .map(|i| {
+                covering += operational_from[i];
+                match (covering > 0, can_be_damaged[i]) {
+                    (true, true) => Spring::Unknown,
+                    (true, false) => Spring::Operational,
+                    (false, _) => Spring::Damaged,
+                }
+            })
+            .collect();
+
+        Some(output)
+    }
+}
+
+/// See [`Row::arrangements`].
+struct Arrangements<'a> {
+    row: Row<'a>,
+    runs: Vec<usize>,
+    possible: Table<Possible>,
+    /// Spring and bundle indices that are left, and the arrangement up until them.
+    stack: Vec<(usize, usize, Vec<Spring>)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<Spring>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let springs = self.row.springs;
+
+        while let Some((i, b, arrangement)) = self.stack.pop() {
+            if i == springs.len() {
+                return Some(arrangement);
+            }
+
+            // Pushed in reverse, so that bundles are placed as early as possible first
+            if springs[i].can_be_damaged() && self.possible.get(i + 1, b).0 {
+                let mut arrangement = arrangement.clone();
+                arrangement.push(Spring::Damaged);
+                self.stack.push((i + 1, b, arrangement));
+            }
+
+            if b < self.row.bundles.len() && springs[i].can_be_operational() {
+                if let Some(next) = self.row.place(i, b, &self.runs) {
+                    if self.possible.get(next, b + 1).0 {
+                        let mut arrangement = arrangement;
+                        arrangement.extend(std::iter::repeat_n(
+                            Spring::Operational,
+                            self.row.bundles[b],
+                        ));
+                        arrangement.resize(next, Spring::Damaged);
+                        self.stack.push((next, b + 1, arrangement));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Where the springs are being read from left to right, with the bundles repeating forever:
+/// how many bundles are done, and how many operational springs are in the current one.
+type State = (i64, usize);
+
+/// Reads one more spring from every state.
+fn advance(
+    states: &BTreeMap<State, Count>,
+    spring: Spring,
+    bundles: &[usize],
+) -> BTreeMap<State, Count> {
+    let size = |bundle: i64| bundles[bundle.rem_euclid(bundles.len() as i64) as usize];
+    let mut output = BTreeMap::<State, Count>::new();
+
+    for (&(bundle, run), &ways) in states {
+        let mut next = |state| {
+            let entry = output.entry(state).or_default();
+            *entry = *entry + ways;
         };
 
-        cache.insert(data, result);
-        result
+        if spring.can_be_damaged() {
+            match run {
+                0 => next((bundle, 0)),
+                run if run == size(bundle) => next((bundle + 1, 0)),
+                _ => {}
+            }
+        }
+
+        if spring.can_be_operational() && run < size(bundle) {
+            next((bundle, run + 1));
+        }
+    }
+
+    output
+}
+
+fn advance_all(
+    mut states: BTreeMap<State, Count>,
+    springs: &[Spring],
+    bundles: &[usize],
+) -> BTreeMap<State, Count> {
+    for &spring in springs {
+        states = advance(&states, spring, bundles);
     }
 
-    execute(Data::default(), springs, bundles, &mut HashMap::new())
+    states
+}
+
+type Matrix = Vec<Vec<Count>>;
+
+fn mul(a: &Matrix, b: &Matrix) -> Matrix {
+    (0..a.len())
+        .map(|i| {
+            (0..b[0].len())
+                .map(|j| (0..b.len()).fold(Count::default(), |sum, k| sum + a[i][k] * b[k][j]))
+                .collect()
+        })
+        .collect()
 }
 
-fn part1(input: &str) -> i64 {
+/// Counts the arrangements of the row unfolded `factor` times without building it.
+///
+/// Every copy of the springs after the first one (with the `?` that joins it) does the same thing
+/// to the state, only shifted by the amount of bundles in a copy. When the states between copies
+/// stay within a handful of possibilities, that makes a small matrix, and raising it to the
+/// `factor - 1`th power gives the answer in `O(log factor)` multiplications.
+///
+/// States that drifted too far from the bundles of their copy to catch up with the copies left are
+/// dropped, since they can't end up as an arrangement. Returns `None` if there are still too many
+/// states after that, in which case the row has to be unfolded.
+fn count_repeated(springs: &[Spring], bundles: &[usize], factor: u64) -> Option<Option<u128>> {
+    const MAX_STATES: usize = 64;
+
+    if bundles.is_empty() {
+        return None;
+    }
+
+    let copy = bundles.len() as i64;
+    let shift = |states: BTreeMap<State, Count>| -> BTreeMap<State, Count> {
+        states
+            .into_iter()
+            .map(|((bundle, run), ways)| ((bundle - copy, run), ways))
+            .collect()
+    };
+
+    let first = shift(advance_all(
+        BTreeMap::from([((0, 0), Count(Some(1)))]),
+        springs,
+        bundles,
+    ));
+
+    let mut joined = vec![Spring::Unknown];
+    joined.extend_from_slice(springs);
+
+    let after_copy = |state: State| {
+        shift(advance_all(
+            BTreeMap::from([(state, Count(Some(1)))]),
+            &joined,
+            bundles,
+        ))
+    };
+
+    // How far a single copy can move the bundle being read from where it should be, which is the
+    // same for every state with the same bundle (up to a multiple of a copy) and run
+    let drifts = bundles
+        .iter()
+        .enumerate()
+        .flat_map(|(bundle, &size)| (0..=size).map(move |run| (bundle as i64, run)))
+        .flat_map(|state| {
+            after_copy(state)
+                .into_keys()
+                .map(move |(bundle, _)| bundle - state.0)
+        })
+        .collect::<Vec<_>>();
+    let min_drift = i128::from(drifts.iter().copied().min().unwrap_or(0));
+    let max_drift = i128::from(drifts.iter().copied().max().unwrap_or(0));
+
+    // Whether a state reached after `depth` copies past the first one (or more) can still get
+    // every bundle done with the copies left, which ends in bundle `0` or `-1`
+    let viable = |(bundle, _): State, depth: u64| {
+        let Some(left) = (factor - 1).checked_sub(depth).map(i128::from) else {
+            return false;
+        };
+
+        let reachable = (left * min_drift).min(0)..=(left * max_drift).max(0);
+        [0, -1]
+            .into_iter()
+            .any(|end| reachable.contains(&i128::from(end - bundle)))
+    };
+
+    // Every state between copies, and where each of them can go after the next copy
+    let mut states = Vec::new();
+    let mut depths = Vec::new();
+    for &state in first.keys() {
+        if viable(state, 0) {
+            states.push(state);
+            depths.push(0);
+        }
+    }
+
+    // The first copy can't be arranged in a way that the others can finish
+    if states.is_empty() {
+        return Some(Some(0));
+    }
+
+    let mut transitions = Vec::new();
+    let mut i = 0;
+    while let Some(&state) = states.get(i) {
+        let depth = depths[i] + 1;
+        // Anything reached from here is at least one copy further than this state first was
+        let mut next = after_copy(state);
+        next.retain(|&next_state, _| states.contains(&next_state) || viable(next_state, depth));
+
+        for &next_state in next.keys() {
+            if !states.contains(&next_state) {
+                states.push(next_state);
+                depths.push(depth);
+            }
+        }
+
+        if states.len() > MAX_STATES {
+            return None;
+        }
+
+        transitions.push(next);
+        i += 1;
+    }
+
+    let index = states
+        .iter()
+        .enumerate()
+        .map(|(i, state)| (*state, i))
+        .collect::<HashMap<_, _>>();
+
+    let mut matrix = vec![vec![Count::default(); states.len()]; states.len()];
+    for (i, next) in transitions.iter().enumerate() {
+        for (state, &ways) in next {
+            matrix[i][index[state]] = ways;
+        }
+    }
+
+    let mut vector = vec![states
+        .iter()
+        .map(|state| first.get(state).copied().unwrap_or_default())
+        .collect::<Vec<_>>()];
+    let mut power = factor - 1;
+    while power > 0 {
+        if power % 2 == 1 {
+            vector = mul(&vector, &matrix);
+        }
+
+        matrix = mul(&matrix, &matrix);
+        power /= 2;
+    }
+
+    // Every bundle has to be done by the end, maybe without a damaged spring after the last one
+    let last = *bundles.last().unwrap();
+    let done = states
+        .iter()
+        .zip(&vector[0])
+        .filter(|(state, _)| **state == (0, 0) || **state == (-1, last))
+        .fold(Count::default(), |sum, (_, &ways)| sum + ways);
+
+    Some(done.0)
+}
+
+/// Why a row couldn't be counted after unfolding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UnfoldError {
+    /// More arrangements than fit in a `u128`.
+    Overflow,
+    /// The row can't be counted without unfolding it, and it would take too much memory.
+    TooLong { length: u64 },
+}
+
+impl fmt::Display for UnfoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "Too many arrangements to fit in a u128"),
+            Self::TooLong { length } => {
+                write!(
+                    f,
+                    "A row unfolded into {length} springs is too long to count"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnfoldError {}
+
+/// Arrangements of the row repeated `factor` times, joined by unknown springs.
+fn count_unfolded(springs: &[Spring], bundles: &[usize], factor: u64) -> Result<u128, UnfoldError> {
+    const MAX_UNFOLDED: u64 = 1 << 20;
+
+    // Nothing left once it's folded into nothing
+    if factor == 0 {
+        return Ok(1);
+    }
+
+    let count = match count_repeated(springs, bundles, factor) {
+        Some(count) => count,
+        None => {
+            let length = (springs.len() as u64 + 1).saturating_mul(factor) - 1;
+            if length > MAX_UNFOLDED {
+                return Err(UnfoldError::TooLong { length });
+            }
+
+            let springs = vec![springs; factor as usize].join(&Spring::Unknown);
+            let bundles = bundles.repeat(factor as usize);
+
+            Row::new(&springs, &bundles).count()
+        }
+    };
+
+    count.ok_or(UnfoldError::Overflow)
+}
+
+fn part1(input: &str) -> u128 {
     input
         .lines()
         .map(|line| {
-            let (beacons, bundles) = parser::line(line).unwrap();
-            count_arrangments(&beacons, &bundles)
+            let (springs, bundles) = parser::line(line).unwrap();
+            Row::new(&springs, &bundles).count()
         })
-        .sum()
+        .try_fold(0u128, |sum, count| sum.checked_add(count?))
+        .expect("Too many arrangements to fit in a u128")
+}
+
+/// How many times the rows get unfolded. Can be changed with `--param unfold=N`.
+fn unfold_factor() -> u64 {
+    christmas_tree::param("unfold").unwrap_or(5)
 }
 
-fn part2(input: &str) -> i64 {
+fn part2(input: &str) -> u128 {
+    let factor = unfold_factor();
+
     input
         .par_lines()
         .map(|line| {
-            let (beacons, bundles) = parser::line(line).unwrap();
-            let beacons = vec![beacons; 5].join(&Spring::Unknown);
-            let bundles = std::iter::repeat(bundles)
-                .take(5)
-                .flatten()
-                .collect::<Vec<_>>();
-
-            count_arrangments(&beacons, &bundles)
+            let (springs, bundles) = parser::line(line).unwrap();
+            count_unfolded(&springs, &bundles, factor)
         })
-        .sum()
+        .try_reduce(|| 0, |a, b| a.checked_add(b).ok_or(UnfoldError::Overflow))
+        .unwrap_or_else(|err| panic!("{err}"))
+}
+
+fn explain(input: &str) {
+    const SHOWN: usize = 5;
+
+    for line in input.lines() {
+        let (springs, bundles) = parser::line(line).unwrap();
+        let row = Row::new(&springs, &bundles);
+
+        match row.count() {
+            Some(count) => println!("{line}: {count} arrangements"),
+            None => println!("{line}: more arrangements than fit in a u128"),
+        }
+        for arrangement in row.arrangements().take(SHOWN) {
+            println!("  {}", to_string(&arrangement));
+        }
+    }
+}
+
+/// Solution of the nonogram with the given clues (see [`nonogram::Clues`]), or why there isn't one.
+fn solve_nonogram(clues: &str) -> String {
+    let clues = match clues.parse::<nonogram::Clues>() {
+        Ok(clues) => clues,
+        Err(err) => return err,
+    };
+
+    match nonogram::solve(&clues.rows, &clues.columns) {
+        Some(grid) => grid.iter().map(|row| to_string(row) + "\n").collect(),
+        None => "This nonogram has no solution".to_owned(),
+    }
+}
+
+/// Interactive prompt that solves nonograms, since they're the same kind of lines as the springs.
+fn repl(_: &str) {
+    let mut stdout = io::stdout();
+
+    println!("Nonogram clues, like `1,1;5;5;3;1/2;4;4;4;2` (rows/columns). Empty line to quit.");
+    print!("> ");
+    stdout.flush().unwrap();
+
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap();
+        if line.trim().is_empty() {
+            break;
+        }
+
+        println!("{}", solve_nonogram(line.trim()).trim_end());
+        print!("> ");
+        stdout.flush().unwrap();
+    }
 }
 
 christmas_tree::examples! {
@@ -174,3 +648,114 @@ christmas_tree::examples! {
         ?###???????? 3,2,1
     " => 21, 525152,
 }
+
+fn to_string(springs: &[Spring]) -> String {
+    springs
+        .iter()
+        .map(|spring| match spring {
+            Spring::Operational => '#',
+            Spring::Damaged => '.',
+            Spring::Unknown => '?',
+        })
+        .collect()
+}
+
+#[test]
+fn arrangements() {
+    let (springs, bundles) = parser::line("?###???????? 3,2,1").unwrap();
+    let row = Row::new(&springs, &bundles);
+
+    let arrangements = row.arrangements().collect::<Vec<_>>();
+    assert_eq!(Some(arrangements.len() as u128), row.count());
+    assert_eq!(to_string(&arrangements[0]), ".###.##.#...");
+    assert!(arrangements.iter().all(|a| a.len() == springs.len()));
+
+    let (springs, bundles) = parser::line("#.# 2").unwrap();
+    assert_eq!(Row::new(&springs, &bundles).arrangements().next(), None);
+}
+
+#[test]
+fn deduce() {
+    let deduce = |line| {
+        let (springs, bundles) = parser::line(line).unwrap();
+        Row::new(&springs, &bundles).deduce().map(|s| to_string(&s))
+    };
+
+    assert_eq!(deduce("?????????? 8"), Some("??######??".to_string()));
+    assert_eq!(deduce("???.### 1,1,3"), Some("#.#.###".to_string()));
+    assert_eq!(deduce("?#??? 1,1"), Some(".#.??".to_string()));
+    assert_eq!(deduce("##? 1"), None);
+}
+
+#[test]
+fn unfolding() {
+    let example = christmas_tree::indoc! {"
+        ???.### 1,1,3
+        .??..??...?##. 1,1,3
+        ?#?#?#?#?#?#?#? 1,3,1,6
+        ????.#...#... 4,1,1
+        ????.######..#####. 1,6,5
+        ?###???????? 3,2,1
+        ?#? 1
+        ??#?? 1,1
+    "};
+
+    for line in example.lines() {
+        let (springs, bundles) = parser::line(line).unwrap();
+
+        for factor in 1..=6 {
+            let unfolded_springs = vec![springs.clone(); factor].join(&Spring::Unknown);
+            let unfolded_bundles = bundles.repeat(factor);
+
+            assert_eq!(
+                count_unfolded(&springs, &bundles, factor as u64).ok(),
+                Row::new(&unfolded_springs, &unfolded_bundles).count(),
+                "{line} unfolded {factor} times"
+            );
+        }
+    }
+
+    let (springs, bundles) = parser::line("#.# 1,1").unwrap();
+    assert_eq!(count_unfolded(&springs, &bundles, 1_000_000_000), Ok(1));
+
+    let (springs, bundles) = parser::line("????? 1").unwrap();
+    assert_eq!(
+        count_unfolded(&springs, &bundles, 100),
+        Err(UnfoldError::Overflow)
+    );
+
+    // Can't be arranged, so there's nothing to overflow
+    let (springs, bundles) = parser::line("## 1").unwrap();
+    assert_eq!(count_unfolded(&springs, &bundles, u64::MAX), Ok(0));
+
+    // The `?`s around the `#` can only be damaged, except for the one joining copies, which
+    // would make a copy go ahead of its bundle for good
+    let (springs, bundles) = parser::line("?#? 1").unwrap();
+    assert_eq!(count_unfolded(&springs, &bundles, 1_000_000), Ok(1));
+    assert_eq!(count_unfolded(&springs, &bundles, 1 << 40), Ok(1));
+
+    // Copies can catch up with each other, so it needs unfolding, which takes way too much memory
+    let (springs, bundles) = parser::line("??#?? 1,1").unwrap();
+    assert_eq!(
+        count_unfolded(&springs, &bundles, 1 << 40),
+        Err(UnfoldError::TooLong {
+            length: (6 << 40) - 1
+        })
+    );
+
+    assert_eq!(Count(None) * Count(Some(0)), Count(Some(0)));
+    assert_eq!(Count(Some(u128::MAX)) * Count(Some(2)), Count(None));
+}
+
+#[test]
+fn nonogram_prompt() {
+    assert_eq!(
+        solve_nonogram("1,1;5;5;3;1/2;4;4;4;2"),
+        ".#.#.\n#####\n#####\n.###.\n..#..\n"
+    );
+    assert_eq!(solve_nonogram("1/2"), "This nonogram has no solution");
+    assert_eq!(
+        solve_nonogram("1,1;5"),
+        "Missing `/` between the rows and the columns"
+    );
+}
@@ -0,0 +1,161 @@
+//! Nonograms (or picross): a grid where every row and column has the sizes of its bundles of
+//! filled cells, like the rows of springs. Each line is narrowed down with [`Row::deduce`] until
+//! nothing changes, and guesses are made when that isn't enough.
+
+use std::str::FromStr;
+
+use super::{Row, Spring};
+
+/// Filled cells are [`Spring::Operational`] and empty ones [`Spring::Damaged`].
+pub type Grid = Vec<Vec<Spring>>;
+
+fn column(grid: &Grid, x: usize) -> Vec<Spring> {
+    grid.iter().map(|row| row[x]).collect()
+}
+
+/// Deduces lines until nothing changes. Returns `false` if some line can't be satisfied.
+fn propagate(grid: &mut Grid, rows: &[Vec<usize>], columns: &[Vec<usize>]) -> bool {
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        for (y, bundles) in rows.iter().enumerate() {
+            let Some(line) = Row::new(&grid[y], bundles).deduce() else {
+                return false;
+            };
+
+            changed |= line != grid[y];
+            grid[y] = line;
+        }
+
+        for (x, bundles) in columns.iter().enumerate() {
+            let current = column(grid, x);
+            let Some(line) = Row::new(&current, bundles).deduce() else {
+                return false;
+            };
+
+            changed |= line != current;
+            for (y, spring) in line.into_iter().enumerate() {
+                grid[y][x] = spring;
+            }
+        }
+    }
+
+    true
+}
+
+fn search(mut grid: Grid, rows: &[Vec<usize>], columns: &[Vec<usize>]) -> Option<Grid> {
+    if !propagate(&mut grid, rows, columns) {
+        return None;
+    }
+
+    let unknown = grid.iter().enumerate().find_map(|(y, row)| {
+        let x = row.iter().position(|s| *s == Spring::Unknown)?;
+        Some((x, y))
+    });
+
+    let Some((x, y)) = unknown else {
+        return Some(grid);
+    };
+
+    [Spring::Operational, Spring::Damaged]
+        .into_iter()
+        .find_map(|guess| {
+            let mut grid = grid.clone();
+            grid[y][x] = guess;
+            search(grid, rows, columns)
+        })
+}
+
+/// A grid that matches the bundles of every row and column, if there's any.
+pub fn solve(rows: &[Vec<usize>], columns: &[Vec<usize>]) -> Option<Grid> {
+    let grid = vec![vec![Spring::Unknown; columns.len()]; rows.len()];
+    search(grid, rows, columns)
+}
+
+/// Bundles of every row and column, written like `1,1;5;5;3;1/2;4;4;4;2`: the rows, then the
+/// columns, with lines separated by `;` and each line's bundles by `,` (an empty or `0` line has
+/// no filled cells).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clues {
+    pub rows: Vec<Vec<usize>>,
+    pub columns: Vec<Vec<usize>>,
+}
+
+impl FromStr for Clues {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = |part: &str| -> Result<Vec<Vec<usize>>, String> {
+            part.split(';')
+                .map(|line| {
+                    line.split(',')
+                        .map(str::trim)
+                        .filter(|bundle| !bundle.is_empty())
+                        .map(|bundle| {
+                            bundle
+                                .parse()
+                                .map_err(|_| format!("Invalid bundle `{bundle}`"))
+                        })
+                        .filter(|bundle| bundle != &Ok(0))
+                        .collect()
+                })
+                .collect()
+        };
+
+        let (rows, columns) = s
+            .split_once('/')
+            .ok_or("Missing `/` between the rows and the columns")?;
+
+        Ok(Self {
+            rows: lines(rows)?,
+            columns: lines(columns)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_picross() {
+        // A heart
+        let rows = [vec![1, 1], vec![5], vec![5], vec![3], vec![1]];
+        let columns = [vec![2], vec![4], vec![4], vec![4], vec![2]];
+
+        let solution = solve(&rows, &columns).unwrap();
+        let drawn = solution
+            .iter()
+            .map(|row| crate::to_string(row))
+            .collect::<Vec<_>>();
+
+        assert_eq!(drawn, [".#.#.", "#####", "#####", ".###.", "..#.."]);
+
+        // Needs guessing: both diagonals fit
+        let solution = solve(&[vec![1], vec![1]], &[vec![1], vec![1]]).unwrap();
+        let drawn = solution
+            .iter()
+            .map(|row| crate::to_string(row))
+            .collect::<Vec<_>>();
+
+        assert_eq!(drawn, ["#.", ".#"]);
+
+        assert_eq!(solve(&[vec![2]], &[vec![1]]), None);
+    }
+
+    #[test]
+    fn parse_clues() {
+        let clues = "1,1;5;5;3;1/2;4;4;4;2".parse::<Clues>().unwrap();
+        assert_eq!(clues.rows[0], [1, 1]);
+        assert_eq!(clues.columns.len(), 5);
+
+        let empty = "0;1/;1".parse::<Clues>().unwrap();
+        assert_eq!(empty.rows, [vec![], vec![1]]);
+        assert_eq!(empty.columns, [vec![], vec![1]]);
+
+        assert!("1;1".parse::<Clues>().is_err());
+        assert!("1;x/1".parse::<Clues>().is_err());
+    }
+}
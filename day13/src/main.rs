@@ -1,114 +1,252 @@
-christmas_tree::day!(13);
+christmas_tree::day!(13, explain);
 
-fn parse(input: &str) -> impl Iterator<Item = (Vec<Vec<bool>>, Vec<Vec<bool>>)> + '_ {
-    input.split("\n\n").map(|block| {
-        let rows: Vec<Vec<bool>> = block
-            .lines()
-            .map(|line| line.chars().map(|c| c == '#').collect())
-            .collect();
+/// Bits of a row or a column, 64 cells per word.
+type Line = Vec<u64>;
 
-        let columns: Vec<Vec<bool>> = (0..rows[0].len())
-            .map(|x| (0..rows.len()).map(|y| rows[y][x]).collect())
-            .collect();
+fn set(line: &mut Line, i: usize) {
+    line[i / 64] |= 1 << (i % 64);
+}
 
-        (rows, columns)
-    })
+/// Rows and columns packed into bits: bit `x` of a row (and bit `y` of a column) is set if that
+/// cell is a rock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    rows: Vec<Line>,
+    columns: Vec<Line>,
 }
 
-fn check_reflections(rows: &[Vec<bool>], index: usize) -> bool {
-    let mut i = 0;
-    loop {
-        if index < i + 1 || index + i >= rows.len() {
-            return true;
+fn parse(input: &str) -> impl Iterator<Item = Pattern> + '_ {
+    input.split("\n\n").map(|block| {
+        let lines = block.lines().collect::<Vec<_>>();
+        let width = lines[0].len();
+
+        let mut rows = vec![vec![0; width.div_ceil(64)]; lines.len()];
+        let mut columns = vec![vec![0; lines.len().div_ceil(64)]; width];
+
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if c == '#' {
+                    set(&mut rows[y], x);
+                    set(&mut columns[x], y);
+                }
+            }
         }
 
-        if rows[(index - i - 1) as usize] != rows[(index + i) as usize] {
-            return false;
-        }
+        Pattern { rows, columns }
+    })
+}
 
-        i += 1;
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    /// Between two columns
+    Vertical,
+    /// Between two rows
+    Horizontal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Reflection {
+    axis: Axis,
+    /// Amount of columns (or rows) before the mirror.
+    index: usize,
+    /// `[x, y]` of the cells that have to be flipped for the reflection to be perfect. Each of them
+    /// could also be fixed on the other side of the mirror, this is the one closest to the start.
+    smudges: Vec<[usize; 2]>,
 }
 
-/// Returns index n. This means that if there's a reflection in n, then c[n] == c[n - 1], c[n + 1] == c[n - 2], ..., c[n + k] == c[n - k - 1].
-fn find_reflections(rows: &[Vec<bool>], ignore: Option<usize>) -> Option<usize> {
-    if rows.len() <= 1 {
-        return None;
+impl Reflection {
+    fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Vertical => self.index,
+            Axis::Horizontal => self.index * 100,
+        }
     }
+}
 
-    let half = rows.len() / 2;
+/// Pairs of lines that end up on top of each other when mirrored after `index` lines, with the
+/// one before the mirror first.
+fn mirrored_pairs(len: usize, index: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..index.min(len - index)).map(move |i| (index - i - 1, index + i))
+}
 
-    let check = |index: usize| {
-        if Some(index) != ignore && check_reflections(rows, index) {
-            Some(index)
-        } else {
-            None
-        }
-    };
+/// Amount of cells that differ between the lines and their reflection after `index` lines.
+fn differences(lines: &[Line], index: usize) -> u32 {
+    mirrored_pairs(lines.len(), index)
+        .flat_map(|(a, b)| lines[a].iter().zip(&lines[b]))
+        .map(|(a, b)| (a ^ b).count_ones())
+        .sum()
+}
 
-    find_reflections(&rows[..half], ignore)
-        .and_then(|first| check(first))
+/// First mirror position where exactly `smudges` cells don't match their reflection.
+fn find_mirror(lines: &[Line], smudges: u32) -> Option<usize> {
+    (1..lines.len()).find(|&index| differences(lines, index) == smudges)
+}
+
+fn find_reflection(pattern: &Pattern, smudges: u32) -> Option<Reflection> {
+    let (axis, lines, index) = find_mirror(&pattern.columns, smudges)
+        .map(|index| (Axis::Vertical, &pattern.columns, index))
         .or_else(|| {
-            find_reflections(&rows[half..], ignore.map(|i| i - half))
-                .and_then(|second| check(half + second))
-        })
-        .or_else(|| check(half))
+            find_mirror(&pattern.rows, smudges)
+                .map(|index| (Axis::Horizontal, &pattern.rows, index))
+        })?;
+
+    let mut cells = Vec::new();
+    for (line, other) in mirrored_pairs(lines.len(), index) {
+        for (word, (a, b)) in lines[line].iter().zip(&lines[other]).enumerate() {
+            let mut diff = a ^ b;
+            while diff != 0 {
+                let bit = word * 64 + diff.trailing_zeros() as usize;
+                diff &= diff - 1;
+
+                cells.push(match axis {
+                    Axis::Vertical => [line, bit],
+                    Axis::Horizontal => [bit, line],
+                });
+            }
+        }
+    }
+    cells.sort_by_key(|&[x, y]| (y, x));
+
+    Some(Reflection {
+        axis,
+        index,
+        smudges: cells,
+    })
 }
 
-fn part1(input: &str) -> usize {
+fn solve(input: &str, smudges: u32) -> usize {
     parse(input)
-        .map(|(rows, columns)| {
-            find_reflections(&columns, None)
-                .or_else(|| find_reflections(&rows, None).map(|v| v * 100))
-                .unwrap()
+        .map(|pattern| {
+            find_reflection(&pattern, smudges)
+                .expect("Pattern without a reflection")
+                .summary()
         })
         .sum()
 }
 
+fn part1(input: &str) -> usize {
+    solve(input, 0)
+}
+
 fn part2(input: &str) -> usize {
-    parse(input)
-        .enumerate()
-        .map(|(i, (mut rows, mut columns))| {
-            let row_len = rows.len();
-            let col_len = columns.len();
+    solve(input, 1)
+}
+
+/// Shows where the mirror of each pattern is, with `--param smudges=k` for a different amount
+/// of smudges than part 2's one.
+fn explain(input: &str) {
+    let smudges = christmas_tree::param("smudges").unwrap_or(1);
+
+    for (i, pattern) in parse(input).enumerate() {
+        let Some(reflection) = find_reflection(&pattern, smudges) else {
+            println!("Pattern {}: no mirror with {smudges} smudges", i + 1);
+            continue;
+        };
+
+        let between = match reflection.axis {
+            Axis::Vertical => "columns",
+            Axis::Horizontal => "rows",
+        };
+
+        let cells = reflection
+            .smudges
+            .iter()
+            .map(|[x, y]| format!("({x}, {y})"))
+            .collect::<Vec<_>>();
+
+        println!(
+            "Pattern {}: mirror between {between} {} and {}, smudges at [{}]",
+            i + 1,
+            reflection.index,
+            reflection.index + 1,
+            cells.join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = christmas_tree::indoc! {"
+    #.##..##.
+    ..#.##.#.
+    ##......#
+    ##......#
+    ..#.##.#.
+    ..##..##.
+    #.#.##.#.
+
+    #...##..#
+    #....#..#
+    ..##..###
+    #####.##.
+    #####.##.
+    ..##..###
+    #....#..#
+"};
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn part1() {
+        assert_eq!(super::part1(super::EXAMPLE), 405);
+    }
+
+    #[test]
+    fn part2() {
+        assert_eq!(super::part2(super::EXAMPLE), 400);
+    }
+}
 
-            let original_row = find_reflections(&rows, None);
-            let original_col = find_reflections(&columns, None);
+#[test]
+fn smudges() {
+    let patterns = parse(EXAMPLE).collect::<Vec<_>>();
 
-            (0..row_len).find_map(|y| {
-                (0..col_len).find_map(|x| {
-                    rows[y][x] = !rows[y][x];
-                    columns[x][y] = !columns[x][y];
+    // Smudges from the puzzle: the top left corner, and the 5th cell of the first row
+    let first = find_reflection(&patterns[0], 1).unwrap();
+    assert_eq!((first.axis, first.index), (Axis::Horizontal, 3));
+    assert_eq!(first.smudges, [[0, 0]]);
 
-                    let refl_row = find_reflections(&rows, original_row).map(|v| v * 100);
-                    let refl_col = find_reflections(&columns, original_col);
+    let second = find_reflection(&patterns[1], 1).unwrap();
+    assert_eq!((second.axis, second.index), (Axis::Horizontal, 1));
+    assert_eq!(second.smudges, [[4, 0]]);
 
-                    rows[y][x] = !rows[y][x];
-                    columns[x][y] = !columns[x][y];
+    // Without any smudges the reflections are perfect
+    assert!(find_reflection(&patterns[0], 0).unwrap().smudges.is_empty());
 
-                    refl_row.or(refl_col)
-                })
-            }).unwrap()
-        })
-        .sum()
+    let two = find_reflection(&patterns[0], 2).unwrap();
+    assert_eq!(two.smudges.len(), 2);
 }
 
-christmas_tree::examples! {
-    "
-        #.##..##.
-        ..#.##.#.
-        ##......#
-        ##......#
-        ..#.##.#.
-        ..##..##.
-        #.#.##.#.
-
-        #...##..#
-        #....#..#
-        ..##..###
-        #####.##.
-        #####.##.
-        ..##..###
-        #....#..#
-    " => 405, 400,
+#[test]
+fn big_patterns() {
+    // 70 random rows of 140 cells, and then the same rows backwards
+    let mut seed = 13u64;
+    let mut random_row = || {
+        (0..140)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                if seed >> 63 == 1 {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>()
+    };
+
+    let mut rows = (0..70).map(|_| random_row()).collect::<Vec<_>>();
+    rows.extend(rows.clone().into_iter().rev());
+    assert_eq!(part1(&rows.join("\n")), 7000);
+
+    // Past the first word of both the rows and the columns
+    let flipped = if rows[100].as_bytes()[100] == b'#' {
+        "."
+    } else {
+        "#"
+    };
+    rows[100].replace_range(100..101, flipped);
+
+    let pattern = parse(&rows.join("\n")).next().unwrap();
+    let reflection = find_reflection(&pattern, 1).unwrap();
+    assert_eq!((reflection.axis, reflection.index), (Axis::Horizontal, 70));
+    assert_eq!(reflection.smudges, [[100, 39]]);
 }
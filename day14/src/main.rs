@@ -1,133 +1,114 @@
-use ndarray::{Array1, Array2, Axis};
+use std::time::Duration;
 
-christmas_tree::day!(14);
+use christmas_tree::{
+    cycle,
+    render::Image,
+    viz::{self, Color},
+};
+use platform::{Direction, Platform};
+use program::Program;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Rock {
-    Round,
-    Cube,
-}
+mod platform;
+mod program;
 
-fn parse(input: &str) -> Array2<Option<Rock>> {
-    let size = (
-        input.lines().next().unwrap().chars().count(),
-        input.lines().count(),
-    );
-
-    let array = input
-        .lines()
-        .flat_map(|line| {
-            line.chars().map(|c| match c {
-                'O' => Some(Rock::Round),
-                '#' => Some(Rock::Cube),
-                '.' => None,
-                _ => panic!("Invalid input ({c})"),
-            })
-        })
-        .collect::<Array1<_>>();
+christmas_tree::day!(14, visualize, render, explain);
 
-    array.into_shape(size).unwrap()
-}
+fn part1(input: &str) -> usize {
+    let mut platform = Platform::parse(input);
 
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
+    platform.tilt(Direction::North);
 
-fn tilt<'a>(grid: &mut Array2<Option<Rock>>, direction: Direction) {
-    let (lanes, reversed) = match direction {
-        Direction::North => (grid.columns_mut(), false),
-        Direction::South => (grid.columns_mut(), true),
-        Direction::West => (grid.rows_mut(), false),
-        Direction::East => (grid.rows_mut(), true),
-    };
+    platform.load(Direction::North)
+}
 
-    for mut row in lanes {
-        let mut spot_available = if reversed { row.len() - 1 } else { 0 } as i64;
+const SPIN_CYCLE: [Direction; 4] = [
+    Direction::North,
+    Direction::West,
+    Direction::South,
+    Direction::East,
+];
 
-        let range = 0..row.len();
+/// Tilts for part 2. Can be changed with `--param tilts=PROGRAM`, see [`program`].
+fn tilts() -> Program {
+    christmas_tree::param("tilts").unwrap_or_else(|| "NWSE*1e9".parse().unwrap())
+}
 
-        let delta = if reversed { -1 } else { 1 };
+fn part2(input: &str) -> usize {
+    let mut platform = Platform::parse(input);
 
-        let callback = |i| {
-            let rock = &row[i];
+    tilts().run(&mut platform);
 
-            match rock {
-                Some(Rock::Round) => {
-                    row[i] = None;
-                    row[spot_available as usize] = Some(Rock::Round);
-                    spot_available += delta;
-                }
-                Some(Rock::Cube) => {
-                    spot_available = i as i64 + delta;
-                }
-                None => (),
-            }
-        };
+    platform.load(Direction::North)
+}
 
-        if reversed {
-            (range.rev()).for_each(callback);
-        } else {
-            range.for_each(callback)
+fn explain(input: &str) {
+    let mut platform = Platform::parse(input);
+    let program = tilts();
+
+    for step in &program.steps {
+        match step.run(&mut platform) {
+            Some(cycle) => println!(
+                "{step}: loops every {} times after {} times",
+                cycle.period, cycle.start
+            ),
+            None => println!("{step}: no loop"),
         }
     }
-}
-
-fn north_stress(grid: &Array2<Option<Rock>>) -> i64 {
-    grid.rows()
-        .into_iter()
-        .enumerate()
-        .map(|(i, row)| {
-            let value = (grid.len_of(Axis(0)) - i) as i64;
-            row.iter()
-                .filter(|rock| matches!(rock, Some(Rock::Round)))
-                .count() as i64
-                * value
-        })
-        .sum()
-}
-
-fn part1(input: &str) -> i64 {
-    let mut grid = parse(input);
 
-    tilt(&mut grid, Direction::North);
+    println!();
+    print!("{platform}");
+    println!();
 
-    north_stress(&grid)
+    for edge in Direction::ALL {
+        println!("Load toward {edge}: {}", platform.load(edge));
+    }
 }
 
-fn part2(input: &str) -> i64 {
-    let mut grid = parse(input);
-    let mut history = Vec::new();
+fn to_viz(platform: &Platform) -> viz::Grid {
+    let floor = &platform.floor;
+    let mut output = viz::Grid::new(floor.width, floor.height);
 
-    const ITERATIONS: usize = 1_000_000_000;
-
-    for i in 1..=ITERATIONS {
-        history.push(grid.clone());
-
-        for dir in [
-            Direction::North,
-            Direction::West,
-            Direction::South,
-            Direction::East,
-        ] {
-            tilt(&mut grid, dir);
+    for y in 0..floor.height {
+        for x in 0..floor.width {
+            let position = [x as i64, y as i64];
+            let char = if floor.is_cube([x, y]) { '#' } else { '.' };
+            output.set(position, char).dim([position]);
         }
+    }
 
-        if let Some(loop_start) = history.iter().position(|g| g == &grid) {
-            let loop_size = i - loop_start;
-            let remaining = ITERATIONS - i;
-            let index = remaining % loop_size + loop_start;
+    for [x, y] in platform.rocks.positions() {
+        let position = [x as i64, y as i64];
+        output.set(position, 'O').highlight([position], Color::Cyan);
+    }
 
-            assert!(loop_start <= index && index < i);
+    output
+}
 
-            return north_stress(&history[index]);
-        }
+/// Every tilt until the spin cycles start repeating.
+fn frames(input: &str) -> impl Iterator<Item = viz::Grid> {
+    let mut platform = Platform::parse(input);
+    let cycle = cycle::hashed(platform.rocks.clone(), |rocks| {
+        SPIN_CYCLE.iter().fold(rocks.clone(), |rocks, &dir| {
+            platform.floor.tilt(&rocks, dir)
+        })
+    });
+
+    let tilts = SPIN_CYCLE
+        .iter()
+        .cycle()
+        .take(4 * (cycle.start + cycle.period));
+    std::iter::once(to_viz(&platform)).chain(tilts.map(move |&dir| {
+        platform.tilt(dir);
+        to_viz(&platform)
+    }))
+}
 
-    }
+fn visualize(input: &str) {
+    viz::animate(frames(input), Duration::from_millis(50));
+}
 
-    north_stress(&grid)
+fn render(input: &str) -> Image {
+    Image::Animation(frames(input).collect())
 }
 
 christmas_tree::examples! {
@@ -144,3 +125,103 @@ christmas_tree::examples! {
         #OO..#....
     " => 136, 64,
 }
+
+#[cfg(test)]
+mod generated {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    fn generate(rng: &mut impl Rng, width: usize, height: usize) -> String {
+        (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| match rng.gen_range(0..10) {
+                        0..=1 => '#',
+                        2..=4 => 'O',
+                        _ => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Rolls every rock one cell at a time until nothing moves.
+    fn tilt_slowly(grid: &mut [Vec<char>], direction: Direction) {
+        let [dx, dy] = match direction {
+            Direction::North => [0, -1],
+            Direction::East => [1, 0],
+            Direction::South => [0, 1],
+            Direction::West => [-1, 0],
+        };
+
+        let (height, width) = (grid.len() as i64, grid[0].len() as i64);
+        let mut moved = true;
+        while moved {
+            moved = false;
+
+            for y in 0..height {
+                for x in 0..width {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if !(0..width).contains(&nx) || !(0..height).contains(&ny) {
+                        continue;
+                    }
+
+                    let (x, y, nx, ny) = (x as usize, y as usize, nx as usize, ny as usize);
+                    if grid[y][x] == 'O' && grid[ny][nx] == '.' {
+                        grid[y][x] = '.';
+                        grid[ny][nx] = 'O';
+                        moved = true;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tilts_match_rolling() {
+        let mut rng = StdRng::seed_from_u64(14);
+
+        for _ in 0..50 {
+            // Wider than a word to have lanes spanning several of them
+            let (width, height) = (rng.gen_range(1..150), rng.gen_range(1..150));
+            let input = generate(&mut rng, width, height);
+
+            let mut platform = Platform::parse(&input);
+            let mut grid = input
+                .lines()
+                .map(|line| line.chars().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+
+            for _ in 0..6 {
+                let direction = Direction::ALL[rng.gen_range(0..4)];
+                platform.tilt(direction);
+                tilt_slowly(&mut grid, direction);
+            }
+
+            let expected = grid
+                .iter()
+                .map(|row| row.iter().collect::<String>() + "\n")
+                .collect::<String>();
+            assert_eq!(platform.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn big_platform() {
+        let input = generate(&mut StdRng::seed_from_u64(1), 200, 200);
+        let mut platform = Platform::parse(&input);
+
+        let cycles = "N,E,NWSE*1e9,W*1e18"
+            .parse::<Program>()
+            .unwrap()
+            .run(&mut platform);
+        assert!(cycles[2].is_some());
+
+        // Tilting the same way again doesn't change anything
+        let load = platform.load(Direction::West);
+        platform.tilt(Direction::West);
+        assert_eq!(platform.load(Direction::West), load);
+    }
+}
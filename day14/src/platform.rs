@@ -0,0 +1,380 @@
+//! Platform with the round rocks packed into bits. Cube rocks never move, so each lane is split
+//! once into the stretches between them, and tilting a lane is just counting the rocks in every
+//! stretch and piling them up at one of its ends.
+
+use std::{fmt, ops::Range};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    pub fn new(char: char) -> Option<Self> {
+        match char {
+            'N' => Some(Self::North),
+            'E' => Some(Self::East),
+            'S' => Some(Self::South),
+            'W' => Some(Self::West),
+            _ => None,
+        }
+    }
+
+    /// Lanes the rocks roll along.
+    fn layout(self) -> Layout {
+        match self {
+            Self::North | Self::South => Layout::Columns,
+            Self::East | Self::West => Layout::Rows,
+        }
+    }
+
+    /// Whether rocks roll toward index `0` of their lane.
+    fn toward_start(self) -> bool {
+        matches!(self, Self::North | Self::West)
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let char = match self {
+            Self::North => 'N',
+            Self::East => 'E',
+            Self::South => 'S',
+            Self::West => 'W',
+        };
+
+        write!(f, "{char}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Layout {
+    Rows,
+    Columns,
+}
+
+/// Bits of a row or a column, 64 cells per word.
+type Lane = Vec<u64>;
+
+fn get(lane: &[u64], i: usize) -> bool {
+    lane[i / 64] >> (i % 64) & 1 == 1
+}
+
+fn set(lane: &mut [u64], i: usize) {
+    lane[i / 64] |= 1 << (i % 64);
+}
+
+/// Words touched by `range`, along with the mask of its bits in them.
+fn masks(range: Range<usize>) -> impl Iterator<Item = (usize, u64)> {
+    (range.start / 64..range.end.div_ceil(64)).map(move |word| {
+        let low = range.start.max(word * 64) - word * 64;
+        let high = range.end.min(word * 64 + 64) - word * 64;
+
+        let mask = match high - low {
+            64 => u64::MAX,
+            len => ((1 << len) - 1) << low,
+        };
+
+        (word, mask)
+    })
+}
+
+fn count(lane: &[u64], range: Range<usize>) -> usize {
+    // Most stretches are short enough to fit in a word
+    if range.start / 64 == (range.end - 1) / 64 {
+        let len = range.end - range.start;
+        let word = lane[range.start / 64] >> (range.start % 64);
+        return (word & (u64::MAX >> (64 - len))).count_ones() as usize;
+    }
+
+    masks(range)
+        .map(|(word, mask)| (lane[word] & mask).count_ones() as usize)
+        .sum()
+}
+
+fn fill(lane: &mut [u64], range: Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+
+    if range.start / 64 == (range.end - 1) / 64 {
+        let len = range.end - range.start;
+        lane[range.start / 64] |= (u64::MAX >> (64 - len)) << (range.start % 64);
+        return;
+    }
+
+    for (word, mask) in masks(range) {
+        lane[word] |= mask;
+    }
+}
+
+fn ones(lane: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    lane.iter().enumerate().flat_map(|(i, &word)| {
+        let mut word = word;
+        std::iter::from_fn(move || {
+            (word != 0).then(|| {
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                i * 64 + bit
+            })
+        })
+    })
+}
+
+/// Transposes a 64x64 matrix of bits in place, by swapping the top right and bottom left
+/// quarters of smaller and smaller blocks.
+fn transpose(block: &mut [u64; 64]) {
+    let mut size = 32;
+    let mut mask = u64::MAX >> 32;
+
+    while size != 0 {
+        let mut k = 0;
+        while k < 64 {
+            let swapped = (block[k] >> size ^ block[k + size]) & mask;
+            block[k] ^= swapped << size;
+            block[k + size] ^= swapped;
+            k = (k + size + 1) & !size;
+        }
+
+        size /= 2;
+        mask ^= mask << size;
+    }
+}
+
+/// Everything about the platform that doesn't move when it's tilted.
+#[derive(Debug, Clone)]
+pub struct Floor {
+    pub width: usize,
+    pub height: usize,
+
+    /// Cube rocks, row by row.
+    cubes: Vec<Lane>,
+
+    /// Stretches without cube rocks in each row and each column.
+    rows: Vec<Vec<Range<usize>>>,
+    columns: Vec<Vec<Range<usize>>>,
+}
+
+/// Round rocks, stored in whichever layout the last tilt needed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rocks {
+    layout: Layout,
+    lanes: Vec<Lane>,
+}
+
+/// Splits `0..len` around the cells where `cube` is true.
+fn stretches(len: usize, cube: impl Fn(usize) -> bool) -> Vec<Range<usize>> {
+    let mut stretches = Vec::new();
+    let mut start = 0;
+
+    for i in 0..=len {
+        if i == len || cube(i) {
+            if start < i {
+                stretches.push(start..i);
+            }
+            start = i + 1;
+        }
+    }
+
+    stretches
+}
+
+impl Floor {
+    /// Size of the lanes in a layout, and how many of them there are.
+    fn shape(&self, layout: Layout) -> (usize, usize) {
+        match layout {
+            Layout::Rows => (self.width, self.height),
+            Layout::Columns => (self.height, self.width),
+        }
+    }
+
+    fn stretches(&self, layout: Layout) -> &[Vec<Range<usize>>] {
+        match layout {
+            Layout::Rows => &self.rows,
+            Layout::Columns => &self.columns,
+        }
+    }
+
+    /// The same rocks, stored in another layout.
+    fn transpose(&self, rocks: &Rocks, layout: Layout) -> Rocks {
+        if rocks.layout == layout {
+            return rocks.clone();
+        }
+
+        let (len, amount) = self.shape(layout);
+        let mut lanes = vec![vec![0; len.div_ceil(64)]; amount];
+
+        // One 64x64 block of bits at a time
+        for (i, chunk) in rocks.lanes.chunks(64).enumerate() {
+            for j in 0..amount.div_ceil(64) {
+                let mut block = [0; 64];
+                for (row, lane) in block.iter_mut().zip(chunk) {
+                    *row = lane[j];
+                }
+
+                transpose(&mut block);
+
+                for (lane, row) in lanes[j * 64..].iter_mut().zip(block) {
+                    lane[i] = row;
+                }
+            }
+        }
+
+        Rocks { layout, lanes }
+    }
+
+    pub fn tilt(&self, rocks: &Rocks, direction: Direction) -> Rocks {
+        let layout = direction.layout();
+        let rocks = self.transpose(rocks, layout);
+
+        let lanes = rocks
+            .lanes
+            .iter()
+            .zip(self.stretches(layout))
+            .map(|(lane, stretches)| {
+                let mut tilted = vec![0; lane.len()];
+
+                for stretch in stretches {
+                    let count = count(lane, stretch.clone());
+
+                    let pile = if direction.toward_start() {
+                        stretch.start..stretch.start + count
+                    } else {
+                        stretch.end - count..stretch.end
+                    };
+
+                    fill(&mut tilted, pile);
+                }
+
+                tilted
+            })
+            .collect();
+
+        Rocks { layout, lanes }
+    }
+
+    pub fn is_cube(&self, [x, y]: [usize; 2]) -> bool {
+        get(&self.cubes[y], x)
+    }
+}
+
+impl Rocks {
+    /// `[x, y]` of every round rock.
+    pub fn positions(&self) -> impl Iterator<Item = [usize; 2]> + '_ {
+        self.lanes.iter().enumerate().flat_map(move |(i, lane)| {
+            ones(lane).map(move |j| match self.layout {
+                Layout::Rows => [j, i],
+                Layout::Columns => [i, j],
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Platform {
+    pub floor: Floor,
+    pub rocks: Rocks,
+}
+
+impl Platform {
+    /// # Panics
+    ///
+    /// If there's anything else than `O`, `#` and `.` in the input, or if the lines don't all
+    /// have the same length.
+    pub fn parse(input: &str) -> Self {
+        let lines = input.lines().collect::<Vec<_>>();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        let height = lines.len();
+
+        let empty = vec![vec![0; width.div_ceil(64)]; height];
+        let (mut cubes, mut round) = (empty.clone(), empty);
+
+        for (y, line) in lines.iter().enumerate() {
+            assert_eq!(
+                line.chars().count(),
+                width,
+                "Line {} has the wrong size",
+                y + 1
+            );
+
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    'O' => set(&mut round[y], x),
+                    '#' => set(&mut cubes[y], x),
+                    '.' => (),
+                    _ => panic!("Invalid input ({c})"),
+                }
+            }
+        }
+
+        let rows = cubes
+            .iter()
+            .map(|row| stretches(width, |x| get(row, x)))
+            .collect();
+        let columns = (0..width)
+            .map(|x| stretches(height, |y| get(&cubes[y], x)))
+            .collect();
+
+        Self {
+            floor: Floor {
+                width,
+                height,
+                cubes,
+                rows,
+                columns,
+            },
+            rocks: Rocks {
+                layout: Layout::Rows,
+                lanes: round,
+            },
+        }
+    }
+
+    pub fn tilt(&mut self, direction: Direction) {
+        self.rocks = self.floor.tilt(&self.rocks, direction);
+    }
+
+    /// Sum of the distance of every round rock to the edge opposite of `edge`, so that rocks
+    /// right against `edge` weigh as much as the platform is long.
+    pub fn load(&self, edge: Direction) -> usize {
+        let Floor { width, height, .. } = self.floor;
+
+        self.rocks
+            .positions()
+            .map(|[x, y]| match edge {
+                Direction::North => height - y,
+                Direction::South => y + 1,
+                Direction::West => width - x,
+                Direction::East => x + 1,
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let round = self.floor.transpose(&self.rocks, Layout::Rows);
+
+        for (y, lane) in round.lanes.iter().enumerate() {
+            for x in 0..self.floor.width {
+                let char = if get(lane, x) {
+                    'O'
+                } else if self.floor.is_cube([x, y]) {
+                    '#'
+                } else {
+                    '.'
+                };
+
+                write!(f, "{char}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
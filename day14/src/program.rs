@@ -0,0 +1,136 @@
+//! Tilt sequences like `NWSE*1e9` or `N,E,N`: comma separated steps, each being a few tilts that
+//! get repeated some amount of times (once if there's no `*`).
+
+use std::{fmt, str::FromStr};
+
+use christmas_tree::cycle::{self, Cycle};
+
+use crate::platform::{Direction, Platform};
+
+peg::parser! {
+    grammar parser() for str {
+        rule _ = [' ' | '\t' | '\n']*
+
+        rule direction() -> Direction
+            = c:['N' | 'E' | 'S' | 'W'] { Direction::new(c).unwrap() }
+
+        rule number() -> usize
+            = n:$(['0'..='9']+) {? n.parse().or(Err("number too big")) }
+
+        rule times() -> usize
+            = base:number() exponent:("e" e:number() { e })? {?
+                u32::try_from(exponent.unwrap_or(0))
+                    .ok()
+                    .and_then(|exponent| 10usize.checked_pow(exponent))
+                    .and_then(|power| base.checked_mul(power))
+                    .ok_or("number too big")
+            }
+
+        rule step() -> Step
+            = tilts:direction()+ times:(_ "*" _ t:times() { t })? {
+                Step { tilts, times: times.unwrap_or(1) }
+            }
+
+        pub rule program() -> Program
+            = _ steps:step() ++ (_ "," _) _ { Program { steps } }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub tilts: Vec<Direction>,
+    pub times: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    pub steps: Vec<Step>,
+}
+
+impl FromStr for Program {
+    type Err = peg::error::ParseError<peg::str::LineCol>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parser::program(s)
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for tilt in &self.tilts {
+            write!(f, "{tilt}")?;
+        }
+
+        if self.times != 1 {
+            write!(f, "*{}", self.times)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{step}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Step {
+    /// Tilts the platform as many times as asked, skipping ahead once the rocks start looping.
+    /// Returns the loop if there was one.
+    pub fn run(&self, platform: &mut Platform) -> Option<Cycle> {
+        let floor = &platform.floor;
+
+        let simulation = cycle::simulate(
+            platform.rocks.clone(),
+            |rocks| {
+                self.tilts
+                    .iter()
+                    .fold(rocks.clone(), |rocks, &tilt| floor.tilt(&rocks, tilt))
+            },
+            self.times,
+        );
+
+        platform.rocks = simulation.state;
+        simulation.cycle
+    }
+}
+
+impl Program {
+    /// Runs every step in order, returning the loop each of them found.
+    pub fn run(&self, platform: &mut Platform) -> Vec<Option<Cycle>> {
+        self.steps.iter().map(|step| step.run(platform)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Direction::*;
+
+    #[test]
+    fn parse() {
+        let program = "NWSE*1e9".parse::<Program>().unwrap();
+        assert_eq!(
+            program.steps,
+            [Step {
+                tilts: vec![North, West, South, East],
+                times: 1_000_000_000,
+            }]
+        );
+
+        let program = "N, E ,N*3".parse::<Program>().unwrap();
+        assert_eq!(program.to_string(), "N,E,N*3");
+
+        assert!("NX".parse::<Program>().is_err());
+        assert!("N*1e30".parse::<Program>().is_err());
+        assert!("".parse::<Program>().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! The Holiday ASCII String Helper Manual Arrangement Procedure (HASHMAP): 256 boxes of lenses,
+//! where each label goes in the box given by its hash.
+
+use std::{array, fmt};
+
+peg::parser! {
+    grammar parser() for str {
+        rule label() -> &'input str
+            = $(['a'..='z' | 'A'..='Z' | '0'..='9']+)
+
+        rule focal_length() -> u8
+            = n:$(['0'..='9']+) {? n.parse().or(Err("focal length")) }
+
+        rule operation() -> Operation<'input>
+            = label:label() "=" focal_length:focal_length() {
+                Operation::Insert { label, focal_length }
+            }
+            / label:label() "-" { Operation::Remove { label } }
+
+        pub rule operations() -> Vec<Operation<'input>>
+            = o:operation() ++ "," { o }
+    }
+}
+
+pub fn hash(input: &str) -> u8 {
+    input.bytes().fold(0, |current, byte| {
+        current.wrapping_add(byte).wrapping_mul(17)
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation<'a> {
+    /// `label=focal_length`
+    Insert { label: &'a str, focal_length: u8 },
+    /// `label-`
+    Remove { label: &'a str },
+}
+
+impl<'a> Operation<'a> {
+    /// A comma separated list of operations, like the puzzle input.
+    pub fn parse_all(
+        input: &'a str,
+    ) -> Result<Vec<Self>, peg::error::ParseError<peg::str::LineCol>> {
+        parser::operations(input.trim())
+    }
+}
+
+impl fmt::Display for Operation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Insert {
+                label,
+                focal_length,
+            } => write!(f, "{label}={focal_length}"),
+            Self::Remove { label } => write!(f, "{label}-"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lens {
+    pub label: String,
+    pub focal_length: u8,
+}
+
+/// Where a lens is, with both numbers starting at `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    pub box_number: usize,
+    pub slot: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct LensBoxes {
+    boxes: [Vec<Lens>; 256],
+}
+
+impl Default for LensBoxes {
+    fn default() -> Self {
+        Self {
+            boxes: array::from_fn(|_| Vec::new()),
+        }
+    }
+}
+
+impl LensBoxes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn find(&self, label: &str) -> Option<Slot> {
+        let box_number = hash(label) as usize;
+        let slot = self.boxes[box_number]
+            .iter()
+            .position(|lens| lens.label == label)?;
+
+        Some(Slot { box_number, slot })
+    }
+
+    /// Replaces the lens with the same label if there is one (returning its focal length), or
+    /// puts the lens behind the others of its box.
+    pub fn insert(&mut self, label: &str, focal_length: u8) -> Option<u8> {
+        match self.find(label) {
+            Some(Slot { box_number, slot }) => {
+                let lens = &mut self.boxes[box_number][slot];
+                Some(std::mem::replace(&mut lens.focal_length, focal_length))
+            }
+            None => {
+                self.boxes[hash(label) as usize].push(Lens {
+                    label: label.to_owned(),
+                    focal_length,
+                });
+                None
+            }
+        }
+    }
+
+    /// Takes out the lens with that label, moving the ones behind it forward.
+    pub fn remove(&mut self, label: &str) -> Option<u8> {
+        let Slot { box_number, slot } = self.find(label)?;
+        Some(self.boxes[box_number].remove(slot).focal_length)
+    }
+
+    /// Where the lens with that label is, and its focal length.
+    pub fn lookup(&self, label: &str) -> Option<(Slot, u8)> {
+        let slot = self.find(label)?;
+        Some((slot, self.boxes[slot.box_number][slot.slot].focal_length))
+    }
+
+    pub fn apply(&mut self, operation: Operation) {
+        match operation {
+            Operation::Insert {
+                label,
+                focal_length,
+            } => {
+                self.insert(label, focal_length);
+            }
+            Operation::Remove { label } => {
+                self.remove(label);
+            }
+        }
+    }
+
+    /// Focusing power of every lens, along with its label.
+    pub fn lens_powers(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.boxes.iter().enumerate().flat_map(|(i, lenses)| {
+            lenses.iter().enumerate().map(move |(j, lens)| {
+                let power = (i + 1) as u64 * (j + 1) as u64 * lens.focal_length as u64;
+                (lens.label.as_str(), power)
+            })
+        })
+    }
+
+    pub fn focusing_power(&self) -> u64 {
+        self.lens_powers().map(|(_, power)| power).sum()
+    }
+}
+
+/// Non empty boxes, the way the puzzle shows them (`Box 3: [ot 7] [ab 5] [pc 6]`).
+impl fmt::Display for LensBoxes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, lenses) in self.boxes.iter().enumerate() {
+            if lenses.is_empty() {
+                continue;
+            }
+
+            write!(f, "Box {i}:")?;
+            for lens in lenses {
+                write!(f, " [{} {}]", lens.label, lens.focal_length)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Applies the operations one by one, with the state of the boxes after each of them.
+pub fn trace(operations: &[Operation], mut output: impl fmt::Write) -> fmt::Result {
+    let mut boxes = LensBoxes::new();
+
+    for &operation in operations {
+        boxes.apply(operation);
+        writeln!(output, "After \"{operation}\":\n{boxes}")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_algorithm() {
+        assert_eq!(hash("HASH"), 52);
+        assert_eq!(hash("rn"), 0);
+        assert_eq!(hash("qp"), 1);
+    }
+
+    #[test]
+    fn exact_labels() {
+        // Same box, and one label contains the other
+        let (short, long) = ("ah", "ahp");
+        assert_eq!(hash(short), hash(long));
+
+        let mut boxes = LensBoxes::new();
+        assert_eq!(boxes.insert(long, 1), None);
+        assert_eq!(boxes.insert(short, 2), None);
+        assert_eq!(boxes.to_string(), "Box 105: [ahp 1] [ah 2]\n");
+
+        assert_eq!(boxes.remove(short), Some(2));
+        assert_eq!(boxes.lookup(long).map(|(_, focal)| focal), Some(1));
+        assert_eq!(boxes.remove(short), None);
+    }
+
+    #[test]
+    fn puzzle_trace() {
+        let operations =
+            Operation::parse_all("rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7").unwrap();
+
+        let mut output = String::new();
+        trace(&operations, &mut output).unwrap();
+
+        let end = christmas_tree::indoc! {r#"
+            After "ot=7":
+            Box 0: [rn 1] [cm 2]
+            Box 3: [ot 7] [ab 5] [pc 6]
+        "#};
+        assert!(output.starts_with("After \"rn=1\":\nBox 0: [rn 1]\n\nAfter \"cm-\":\n"));
+        assert!(output.ends_with(&format!("{end}\n")));
+    }
+}
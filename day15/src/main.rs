@@ -1,64 +1,135 @@
-use std::array;
+use std::io::{self, BufRead, Write};
 
-christmas_tree::day!(15);
+use lens_boxes::{hash, LensBoxes, Operation};
 
-fn hash(input: &str) -> i64 {
-    let mut current = 0;
-    for byte in input.bytes() {
-        current += byte as i64;
-        current *= 17;
-        current %= 256;
-    }
+mod lens_boxes;
+
+christmas_tree::day!(15, repl, explain);
 
-    current
+fn part1(input: &str) -> u64 {
+    input.trim().split(',').map(|s| hash(s) as u64).sum()
 }
 
-fn part1(input: &str) -> i64 {
-    input.trim().split(',').map(|s| hash(s)).sum()
+fn part2(input: &str) -> u64 {
+    let mut boxes = LensBoxes::new();
+
+    for operation in Operation::parse_all(input).unwrap() {
+        boxes.apply(operation);
+    }
+
+    boxes.focusing_power()
 }
 
-fn part2<'a>(input: &'a str) -> i64 {
-    let mut boxes: [_; 256] = array::from_fn(|_| Vec::<(&str, i64)>::new());
+/// The boxes after every step, like in the puzzle.
+fn explain(input: &str) {
+    let mut output = String::new();
+    lens_boxes::trace(&Operation::parse_all(input).unwrap(), &mut output).unwrap();
 
-    for s in input.trim().split(',') {
-        None.or_else(|| {
-            let mut iter = s.split('=');
-            let chars = iter.next().unwrap();
-            let hash = hash(chars);
+    print!("{output}");
+}
 
-            let value = iter.next()?.parse::<i64>().unwrap();
+const HELP: &str = "\
+Commands:
+  rn=1,cm-   apply operations
+  ? rn       look up a lens
+  power      focusing power of every lens
+  input      apply the whole puzzle input
+  reset      empty every box
+  quit";
 
-            let bx = &mut boxes[hash as usize];
+/// Answer to one line of the REPL, `None` to stop.
+fn command(boxes: &mut LensBoxes, input: &str, line: &str) -> Option<String> {
+    let line = line.trim();
 
-            if let Some((_, v)) = bx.iter_mut().find(|(s, _)| s.contains(chars)) {
-                *v = value;
-            } else {
-                boxes[hash as usize].push((chars, value));
+    let output = match line {
+        "quit" | "exit" => return None,
+        "" => boxes.to_string(),
+        "help" => HELP.to_owned(),
+        "power" => {
+            let mut output = String::new();
+            for (label, power) in boxes.lens_powers() {
+                output += &format!("{label}: {power}\n");
             }
+            output + &format!("Total: {}", boxes.focusing_power())
+        }
+        "input" => {
+            for operation in Operation::parse_all(input).unwrap() {
+                boxes.apply(operation);
+            }
+            boxes.to_string()
+        }
+        "reset" => {
+            *boxes = LensBoxes::new();
+            "All boxes are empty".to_owned()
+        }
+        _ => match line.strip_prefix('?') {
+            Some(label) => match boxes.lookup(label.trim()) {
+                Some((slot, focal_length)) => format!(
+                    "Box {}, slot {}, focal length {focal_length}",
+                    slot.box_number,
+                    slot.slot + 1
+                ),
+                None => format!("No lens labelled `{}`", label.trim()),
+            },
+            None => match Operation::parse_all(line) {
+                Ok(operations) => {
+                    let mut output = String::new();
+                    for operation in operations {
+                        boxes.apply(operation);
+                        output += &format!("After \"{operation}\":\n{boxes}\n");
+                    }
+                    output
+                }
+                Err(err) => format!("Invalid command ({err}), try `help`"),
+            },
+        },
+    };
+
+    Some(output)
+}
 
-            Some(())
-        })
-        .unwrap_or_else(|| {
-            let mut iter = s.split('-');
-            let chars = iter.next().unwrap();
-            let hash = hash(chars);
+/// Interactive prompt to try operations on the boxes.
+fn repl(input: &str) {
+    let mut boxes = LensBoxes::new();
+    let mut stdout = io::stdout();
 
-            let bx = &mut boxes[hash as usize];
-            bx.retain(|(s, _)| !s.contains(chars));
-        });
-    }
+    println!("{HELP}");
+    print!("> ");
+    stdout.flush().unwrap();
 
-    boxes
-        .iter()
-        .enumerate()
-        .flat_map(|(i, lens)| {
-            lens.iter()
-                .enumerate()
-                .map(move |(j, &(_, val))| (i + 1) as i64 * (j + 1) as i64 * val)
-        })
-        .sum()
+    for line in io::stdin().lock().lines() {
+        let Some(output) = command(&mut boxes, input, &line.unwrap()) else {
+            break;
+        };
+
+        println!("{}", output.trim_end());
+        print!("> ");
+        stdout.flush().unwrap();
+    }
 }
 
 christmas_tree::examples! {
     "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7" => 1320, 145,
 }
+
+#[test]
+fn commands() {
+    let input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+    let mut boxes = LensBoxes::new();
+
+    let mut run = |line| command(&mut boxes, input, line);
+
+    assert_eq!(
+        run("input").unwrap(),
+        "Box 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n"
+    );
+    assert_eq!(run("? ab").unwrap(), "Box 3, slot 2, focal length 5");
+    assert_eq!(
+        run("ab-").unwrap(),
+        "After \"ab-\":\nBox 0: [rn 1] [cm 2]\nBox 3: [ot 7] [pc 6]\n\n"
+    );
+    assert_eq!(run("?ab").unwrap(), "No lens labelled `ab`");
+    assert!(run("power").unwrap().ends_with("Total: 81"));
+    assert!(run("ab=").unwrap().starts_with("Invalid command"));
+    assert_eq!(run("quit"), None);
+}
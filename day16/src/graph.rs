@@ -0,0 +1,271 @@
+//! Answers every start at once: beams only change direction on tiles, so the contraption is a
+//! graph whose nodes are a tile along with the direction the beam enters it, and whose edges are
+//! the straight segments between tiles. Loops of nodes all energize the same cells, so they get
+//! condensed into their strongly connected components, and the cells energized from each
+//! component are the union of its own cells and those of the components it leads to.
+
+use std::collections::{HashMap, HashSet};
+
+use glam::I64Vec2;
+
+use crate::{Beam, Map};
+
+/// A set of cells, one bit per cell of the map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitset(Vec<u64>);
+
+impl Bitset {
+    fn new(len: usize) -> Self {
+        Self(vec![0; len.div_ceil(64)])
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    fn union(&mut self, other: &Self) {
+        for (word, other) in self.0.iter_mut().zip(&other.0) {
+            *word |= other;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.0.len() * 64).filter(|&i| self.0[i / 64] >> (i % 64) & 1 == 1)
+    }
+}
+
+/// Where a beam enters the contraption, and which way it goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Start {
+    pub position: I64Vec2,
+    pub beam: Beam,
+}
+
+/// Every start from the edges, going inside.
+pub fn edge_starts(size: I64Vec2) -> impl Iterator<Item = Start> {
+    let start = |position: [i64; 2], vertical, positive| Start {
+        position: position.into(),
+        beam: Beam::new(vertical, positive),
+    };
+
+    (0..size.x)
+        .flat_map(move |x| {
+            [
+                start([x, 0], true, true),
+                start([x, size.y - 1], true, false),
+            ]
+        })
+        .chain((0..size.y).flat_map(move |y| {
+            [
+                start([0, y], false, true),
+                start([size.x - 1, y], false, false),
+            ]
+        }))
+}
+
+pub struct Energizer<'a> {
+    map: &'a Map,
+
+    /// Index of every tile, so that node `4 * tile + beam.index()` is that tile entered by that
+    /// beam.
+    tiles: HashMap<I64Vec2, usize>,
+
+    /// Component of every node.
+    components: Vec<usize>,
+
+    /// Cells energized by a beam entering any node of a component.
+    energized: Vec<Bitset>,
+}
+
+impl<'a> Energizer<'a> {
+    pub fn new(map: &'a Map) -> Self {
+        let mut positions = map.tiles.keys().copied().collect::<Vec<_>>();
+        positions.sort_by_key(|pos| (pos.y, pos.x));
+
+        let tiles = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &pos)| (pos, i))
+            .collect();
+
+        let mut energizer = Self {
+            map,
+            tiles,
+            components: Vec::new(),
+            energized: Vec::new(),
+        };
+
+        // Cells lit by every node until the beams reach other tiles, and those other tiles
+        let (cells, edges): (Vec<_>, Vec<_>) = (0..positions.len() * 4)
+            .map(|node| {
+                let pos = positions[node / 4];
+                let mut cells = vec![energizer.cell(pos)];
+                let mut next = Vec::new();
+
+                for beam in map.tiles[&pos].reflect(Beam::ALL[node % 4]) {
+                    let (segment, tile) = energizer.walk(pos + beam.delta(), beam);
+                    cells.extend(segment);
+                    next.extend(tile);
+                }
+
+                (cells, next)
+            })
+            .unzip();
+
+        let components = tarjan(&edges);
+        let count = components.iter().max().map_or(0, |&max| max + 1);
+
+        // Components are numbered in reverse topological order, so every component only leads
+        // to components that are already done.
+        let mut members = vec![Vec::new(); count];
+        for (node, &component) in components.iter().enumerate() {
+            members[component].push(node);
+        }
+
+        let cell_count = (map.size.x * map.size.y) as usize;
+        let mut energized: Vec<Bitset> = Vec::with_capacity(count);
+        for nodes in &members {
+            let mut set = Bitset::new(cell_count);
+
+            for &node in nodes {
+                for &cell in &cells[node] {
+                    set.insert(cell);
+                }
+
+                for &next in &edges[node] {
+                    if components[next] != energized.len() {
+                        set.union(&energized[components[next]]);
+                    }
+                }
+            }
+
+            energized.push(set);
+        }
+
+        energizer.components = components;
+        energizer.energized = energized;
+        energizer
+    }
+
+    fn cell(&self, pos: I64Vec2) -> usize {
+        (pos.y * self.map.size.x + pos.x) as usize
+    }
+
+    fn position(&self, cell: usize) -> I64Vec2 {
+        let cell = cell as i64;
+        I64Vec2::new(cell % self.map.size.x, cell / self.map.size.x)
+    }
+
+    /// Cells lit by a beam going straight from `pos`, until it either leaves the map or reaches
+    /// a tile (which is returned as a node).
+    fn walk(&self, mut pos: I64Vec2, beam: Beam) -> (Vec<usize>, Option<usize>) {
+        let mut cells = Vec::new();
+
+        while self.map.contains(pos) {
+            if let Some(&tile) = self.tiles.get(&pos) {
+                return (cells, Some(4 * tile + beam.index()));
+            }
+
+            cells.push(self.cell(pos));
+            pos += beam.delta();
+        }
+
+        (cells, None)
+    }
+
+    pub fn energized(&self, start: Start) -> Bitset {
+        let mut set = Bitset::new((self.map.size.x * self.map.size.y) as usize);
+        let (cells, node) = self.walk(start.position, start.beam);
+
+        for cell in cells {
+            set.insert(cell);
+        }
+
+        if let Some(node) = node {
+            set.union(&self.energized[self.components[node]]);
+        }
+
+        set
+    }
+
+    /// The edge start that energizes the most cells, along with those cells.
+    pub fn best(&self) -> (Start, HashSet<I64Vec2>) {
+        let (start, set) = edge_starts(self.map.size)
+            .map(|start| (start, self.energized(start)))
+            .max_by_key(|(_, set)| set.len())
+            .expect("Empty map");
+
+        let cells = set.iter().map(|cell| self.position(cell)).collect();
+        (start, cells)
+    }
+}
+
+/// Strongly connected component of every node, numbered in the order Tarjan's algorithm finds
+/// them (so a component never has edges to components with a bigger number).
+fn tarjan(edges: &[Vec<usize>]) -> Vec<usize> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut index = vec![UNVISITED; edges.len()];
+    let mut low = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut components = vec![UNVISITED; edges.len()];
+
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut next_component = 0;
+
+    for root in 0..edges.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+
+        // Nodes being explored, with how many of their edges have been followed
+        let mut calls = vec![(root, 0)];
+
+        while let Some(&mut (node, ref mut edge)) = calls.last_mut() {
+            if *edge == 0 {
+                index[node] = next_index;
+                low[node] = next_index;
+                next_index += 1;
+                stack.push(node);
+                on_stack[node] = true;
+            }
+
+            if let Some(&next) = edges[node].get(*edge) {
+                *edge += 1;
+
+                if index[next] == UNVISITED {
+                    calls.push((next, 0));
+                } else if on_stack[next] {
+                    low[node] = low[node].min(index[next]);
+                }
+
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+
+            if low[node] == index[node] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    components[member] = next_component;
+
+                    if member == node {
+                        break;
+                    }
+                }
+
+                next_component += 1;
+            }
+        }
+    }
+
+    components
+}
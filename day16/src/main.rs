@@ -1,10 +1,18 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
+use christmas_tree::{
+    render::Image,
+    viz::{Color, Grid},
+};
 use either::Either;
 use glam::I64Vec2;
-use rayon::prelude::*;
+use graph::{Energizer, Start};
+use optics::{Contraption, ParseError, TileSet};
 
-christmas_tree::day!(16);
+mod graph;
+mod optics;
+
+christmas_tree::day!(16, visualize, render, explain);
 
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 enum Tile {
@@ -18,6 +26,17 @@ enum Tile {
 }
 
 impl Tile {
+    /// The puzzle's tiles are `|`, `-`, `/` and `\`.
+    pub fn from_char(char: char) -> Option<Self> {
+        match char {
+            '|' => Some(Tile::Splitter { vertical: true }),
+            '-' => Some(Tile::Splitter { vertical: false }),
+            '/' => Some(Tile::Mirror { forward: true }),
+            '\\' => Some(Tile::Mirror { forward: false }),
+            _ => None,
+        }
+    }
+
     pub fn reflect(&self, beam: Beam) -> impl Iterator<Item = Beam> {
         let result = match self {
             Tile::Mirror { forward } => Beam {
@@ -45,7 +64,15 @@ struct Beam {
 }
 
 impl Beam {
-    pub fn new(vertical: bool, positive: bool) -> Self {
+    /// In the order of [`Beam::index`].
+    pub const ALL: [Self; 4] = [
+        Self::new(false, false),
+        Self::new(false, true),
+        Self::new(true, false),
+        Self::new(true, true),
+    ];
+
+    pub const fn new(vertical: bool, positive: bool) -> Self {
         Self { vertical, positive }
     }
 
@@ -53,105 +80,124 @@ impl Beam {
         let dir = if self.positive { 1 } else { -1 };
         if self.vertical { [0, dir] } else { [dir, 0] }.into()
     }
-}
-
-fn parse(input: &str) -> HashMap<I64Vec2, Tile> {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars().enumerate().filter_map(move |(x, c)| {
-                let pos = I64Vec2::new(x as i64, y as i64);
-                let tile = match c {
-                    '|' => Tile::Splitter { vertical: true },
-                    '-' => Tile::Splitter { vertical: false },
-                    '/' => Tile::Mirror { forward: true },
-                    '\\' => Tile::Mirror { forward: false },
-                    '.' => return None,
-                    _ => panic!("Invalid tile ({c})"),
-                };
-
-                Some((pos, tile))
-            })
-        })
-        .collect()
-}
-
-fn energize(start: I64Vec2, beam: Beam, map: &HashMap<I64Vec2, Tile>) -> HashSet<I64Vec2> {
-    let mut energized = HashSet::new();
-    let mut history = HashSet::new();
-
-    let map_size = I64Vec2 {
-        x: map.keys().map(|pos| pos.x).max().unwrap(),
-        y: map.keys().map(|pos| pos.y).max().unwrap(),
-    };
 
-    let mut queue = vec![(start, beam)];
+    pub fn index(&self) -> usize {
+        2 * self.vertical as usize + self.positive as usize
+    }
+}
 
-    while let Some((pos, beam)) = queue.pop() {
-        if !history.insert((pos, beam))
-            || pos.x < 0
-            || pos.y < 0
-            || pos.x > map_size.x
-            || pos.y > map_size.y
-        {
-            continue;
-        }
+struct Map {
+    /// Width and height, which can be more than the tiles span if the edges are empty.
+    size: I64Vec2,
+    tiles: HashMap<I64Vec2, Tile>,
+}
 
-        energized.insert(pos);
+impl Map {
+    fn contains(&self, pos: I64Vec2) -> bool {
+        pos.cmpge(I64Vec2::ZERO).all() && pos.cmplt(self.size).all()
+    }
+}
 
-        let mut schedule_next = |beam: Beam| queue.push((pos + beam.delta(), beam));
+/// Width and height of the contraption.
+fn size(input: &str) -> I64Vec2 {
+    I64Vec2::new(
+        input.lines().next().map_or(0, |line| line.chars().count()) as i64,
+        input.lines().count() as i64,
+    )
+}
 
-        if let Some(tile) = map.get(&pos) {
-            for beam in tile.reflect(beam) {
-                schedule_next(beam);
+/// Only the puzzle's tiles, since the graph in [`graph`] needs them to always reflect beams the
+/// same way.
+fn parse(input: &str) -> Result<Map, ParseError> {
+    let mut tiles = HashMap::new();
+
+    for (y, line) in input.lines().enumerate() {
+        for (x, char) in line.chars().enumerate() {
+            let position = I64Vec2::new(x as i64, y as i64);
+
+            if char != '.' {
+                let tile = Tile::from_char(char).ok_or(ParseError::UnknownTile {
+                    tile: char,
+                    position,
+                })?;
+                tiles.insert(position, tile);
             }
-        } else {
-            schedule_next(beam);
         }
     }
 
-    energized
+    Ok(Map {
+        size: size(input),
+        tiles,
+    })
 }
 
+/// Where the puzzle's beam enters the contraption.
+const START: Start = Start {
+    position: I64Vec2::ZERO,
+    beam: Beam::new(false, true),
+};
+
 fn part1(input: &str) -> i64 {
-    let map = parse(input);
-
-    energize(
-        [0, 0].into(),
-        Beam {
-            vertical: false,
-            positive: true,
-        },
-        &map,
-    )
-    .len() as i64
+    let mut contraption =
+        Contraption::parse(input, &TileSet::puzzle()).unwrap_or_else(|err| panic!("{err}"));
+
+    contraption.fire(START).len() as i64
 }
 
 fn part2(input: &str) -> i64 {
-    let map = parse(input);
-    let map_size = I64Vec2 {
-        x: map.keys().map(|pos| pos.x).max().unwrap(),
-        y: map.keys().map(|pos| pos.y).max().unwrap(),
+    let map = parse(input).unwrap_or_else(|err| panic!("{err}"));
+    let (_, energized) = Energizer::new(&map).best();
+
+    energized.len() as i64
+}
+
+fn explain(input: &str) {
+    let map = parse(input).unwrap_or_else(|err| panic!("{err}"));
+    let (start, energized) = Energizer::new(&map).best();
+
+    let direction = match (start.beam.vertical, start.beam.positive) {
+        (true, true) => "down",
+        (true, false) => "up",
+        (false, true) => "right",
+        (false, false) => "left",
     };
 
-    (0..=map_size.x)
-        .into_par_iter()
-        .flat_map(|x| {
-            [
-                ([x, 0], Beam::new(true, true)),
-                ([x, map_size.y], Beam::new(true, false)),
-            ]
-        })
-        .chain((0..=map_size.y).into_par_iter().flat_map(|y| {
-            [
-                ([0, y], Beam::new(false, true)),
-                ([map_size.x, y], Beam::new(false, false)),
-            ]
-        }))
-        .map(|(pos, beam)| energize(pos.into(), beam, &map).len())
-        .max()
-        .unwrap() as i64
+    println!(
+        "Best start: going {direction} from ({}, {}), energizing {} tiles",
+        start.position.x,
+        start.position.y,
+        energized.len()
+    );
+
+    let mut grid = Grid::from_text(input);
+    grid.region(energized, Color::Yellow)
+        .highlight(map.tiles.keys().copied(), Color::Blue)
+        .highlight([start.position], Color::Red);
+    grid.print();
+}
+
+/// Fires from the puzzle's start. Other tiles can be used with `--param tiles=extended` (see
+/// [`TileSet::extended`]).
+fn draw(input: &str) -> Grid {
+    let tile_set = christmas_tree::param("tiles").unwrap_or_else(TileSet::puzzle);
+    let mut contraption =
+        Contraption::parse(input, &tile_set).unwrap_or_else(|err| panic!("{err}"));
+
+    let energized = contraption.fire(START);
+
+    let mut grid = Grid::from_text(input);
+    grid.region(energized, Color::Yellow)
+        .highlight(contraption.tiles(), Color::Blue);
+
+    grid
+}
+
+fn visualize(input: &str) {
+    draw(input).print();
+}
+
+fn render(input: &str) -> Image {
+    Image::Grid(draw(input))
 }
 
 christmas_tree::examples! {
@@ -168,3 +214,39 @@ christmas_tree::examples! {
         ..//.|....
     " => 46, 51,
 }
+
+#[test]
+fn graph_matches_simulation() {
+    let input = christmas_tree::indoc! {r"
+        .|...\....
+        |.-.\.....
+        .....|-...
+        ........|.
+        ..........
+        .........\
+        ..../.\\..
+        .-.-/..|..
+        .|....-|.\
+        ..//.|....
+        ..........
+    "};
+
+    let map = parse(input).unwrap();
+    let energizer = Energizer::new(&map);
+
+    // The puzzle's tiles don't change, so the same contraption can be fired from everywhere
+    let mut contraption = Contraption::parse(input, &TileSet::puzzle()).unwrap();
+
+    let mut most = 0;
+    for start in graph::edge_starts(map.size) {
+        let expected = contraption.fire(start);
+        let energized = energizer.energized(start);
+
+        assert_eq!(energized.len(), expected.len(), "{start:?}");
+        most = most.max(expected.len());
+    }
+
+    let (start, energized) = energizer.best();
+    assert_eq!(energized, contraption.fire(start));
+    assert_eq!(energized.len(), most);
+}
@@ -0,0 +1,444 @@
+//! Tiles beyond the puzzle's mirrors and splitters, for making up new contraptions. Tiles are
+//! trait objects created from their character by a [`TileSet`], and can change whenever a beam
+//! hits them, so firing at the same [`Contraption`] twice doesn't always energize the same cells.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    str::FromStr,
+};
+
+use glam::I64Vec2;
+
+use crate::{graph::Start, Beam, Tile};
+
+/// Where a beam goes after hitting a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// Leaves the tile that way.
+    Here(Beam),
+    /// Leaves the other end of the portal that way. Tiles without a [`Optic::link`] absorb it.
+    Portal(Beam),
+}
+
+pub trait Optic: fmt::Debug {
+    /// Beams leaving the tile when `beam` enters it. Called for every hit, in the order the beams
+    /// reach the tile.
+    fn hit(&mut self, beam: Beam) -> Vec<Exit>;
+
+    /// Anything that changes how the tile reflects beams. A beam entering a tile the same way
+    /// while every tile is in the same state counts as a loop, so this must only take a few
+    /// values.
+    fn state(&self) -> u64 {
+        0
+    }
+
+    /// Tiles with the same link are the two ends of a portal.
+    fn link(&self) -> Option<char> {
+        None
+    }
+}
+
+impl Optic for Tile {
+    fn hit(&mut self, beam: Beam) -> Vec<Exit> {
+        self.reflect(beam).map(Exit::Here).collect()
+    }
+}
+
+/// Stops every beam.
+#[derive(Debug, Clone, Copy)]
+pub struct Absorber;
+
+impl Optic for Absorber {
+    fn hit(&mut self, _: Beam) -> Vec<Exit> {
+        Vec::new()
+    }
+}
+
+/// Lets beams going one way through, and absorbs the others.
+#[derive(Debug, Clone, Copy)]
+pub struct Gate {
+    pub direction: Beam,
+}
+
+impl Optic for Gate {
+    fn hit(&mut self, beam: Beam) -> Vec<Exit> {
+        if beam == self.direction {
+            vec![Exit::Here(beam)]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Mirror that turns from `/` to `\` (and back) every time a beam hits it.
+#[derive(Debug, Clone, Copy)]
+pub struct ToggleMirror {
+    pub forward: bool,
+}
+
+impl Optic for ToggleMirror {
+    fn hit(&mut self, beam: Beam) -> Vec<Exit> {
+        let exits = Tile::Mirror {
+            forward: self.forward,
+        }
+        .hit(beam);
+        self.forward = !self.forward;
+
+        exits
+    }
+
+    fn state(&self) -> u64 {
+        self.forward as u64
+    }
+}
+
+/// Beams entering one end leave from the other one, going the same way.
+#[derive(Debug, Clone, Copy)]
+pub struct Portal {
+    pub label: char,
+}
+
+impl Optic for Portal {
+    fn hit(&mut self, beam: Beam) -> Vec<Exit> {
+        vec![Exit::Portal(beam)]
+    }
+
+    fn link(&self) -> Option<char> {
+        Some(self.label)
+    }
+}
+
+type Factory = Box<dyn Fn(char) -> Box<dyn Optic>>;
+
+/// Which tile each character of a contraption stands for (`.` is always empty space).
+#[derive(Default)]
+pub struct TileSet {
+    factories: HashMap<char, Factory>,
+}
+
+impl TileSet {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Adds (or replaces) a tile, made by calling `factory` with its character.
+    pub fn with(mut self, char: char, factory: impl Fn(char) -> Box<dyn Optic> + 'static) -> Self {
+        self.factories.insert(char, Box::new(factory));
+        self
+    }
+
+    /// Splitters (`|` and `-`) and mirrors (`/` and `\`).
+    pub fn puzzle() -> Self {
+        ['|', '-', '/', '\\']
+            .into_iter()
+            .fold(Self::empty(), |set, char| {
+                set.with(char, |char| Box::new(Tile::from_char(char).unwrap()))
+            })
+    }
+
+    /// The puzzle's tiles, along with absorbers (`#`), gates (`>`, `<`, `^` and `v`), mirrors
+    /// that toggle when hit (`%` starts as `/`, `&` as `\`) and portals (pairs of digits).
+    pub fn extended() -> Self {
+        let gates = [
+            ('>', Beam::new(false, true)),
+            ('<', Beam::new(false, false)),
+            ('^', Beam::new(true, false)),
+            ('v', Beam::new(true, true)),
+        ];
+
+        let set = gates
+            .into_iter()
+            .fold(Self::puzzle(), |set, (char, direction)| {
+                set.with(char, move |_| Box::new(Gate { direction }))
+            });
+
+        ('0'..='9').fold(
+            set.with('#', |_| Box::new(Absorber))
+                .with('%', |_| Box::new(ToggleMirror { forward: true }))
+                .with('&', |_| Box::new(ToggleMirror { forward: false })),
+            |set, char| set.with(char, |label| Box::new(Portal { label })),
+        )
+    }
+
+    fn create(&self, char: char) -> Option<Box<dyn Optic>> {
+        self.factories.get(&char).map(|factory| factory(char))
+    }
+}
+
+/// By name, so it can be picked with `--param tiles=extended`.
+impl FromStr for TileSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "puzzle" => Ok(Self::puzzle()),
+            "extended" => Ok(Self::extended()),
+            _ => Err(format!(
+                "Unknown tile set `{s}` (expected `puzzle` or `extended`)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    UnknownTile {
+        tile: char,
+        position: I64Vec2,
+    },
+    /// Portals need exactly two ends.
+    UnpairedPortal {
+        label: char,
+        ends: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownTile { tile, position } => {
+                write!(f, "Unknown tile `{tile}` at {}, {}", position.x, position.y)
+            }
+            Self::UnpairedPortal { label, ends } => {
+                write!(f, "Portal `{label}` has {ends} ends instead of 2")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug)]
+pub struct Contraption {
+    pub size: I64Vec2,
+    tiles: HashMap<I64Vec2, Box<dyn Optic>>,
+
+    /// Other end of every portal.
+    portals: HashMap<I64Vec2, I64Vec2>,
+}
+
+impl Contraption {
+    pub fn parse(input: &str, tile_set: &TileSet) -> Result<Self, ParseError> {
+        let mut tiles = HashMap::new();
+        for (y, line) in input.lines().enumerate() {
+            for (x, char) in line.chars().enumerate() {
+                let position = I64Vec2::new(x as i64, y as i64);
+
+                if char != '.' {
+                    let tile = tile_set.create(char).ok_or(ParseError::UnknownTile {
+                        tile: char,
+                        position,
+                    })?;
+                    tiles.insert(position, tile);
+                }
+            }
+        }
+
+        let mut ends = HashMap::<_, Vec<_>>::new();
+        for (&position, tile) in &tiles {
+            if let Some(label) = tile.link() {
+                ends.entry(label).or_default().push(position);
+            }
+        }
+
+        let mut portals = HashMap::new();
+        for (label, ends) in ends {
+            let &[a, b] = &ends[..] else {
+                return Err(ParseError::UnpairedPortal {
+                    label,
+                    ends: ends.len(),
+                });
+            };
+
+            portals.insert(a, b);
+            portals.insert(b, a);
+        }
+
+        Ok(Self {
+            size: crate::size(input),
+            tiles,
+            portals,
+        })
+    }
+
+    pub fn contains(&self, pos: I64Vec2) -> bool {
+        pos.cmpge(I64Vec2::ZERO).all() && pos.cmplt(self.size).all()
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = I64Vec2> + '_ {
+        self.tiles.keys().copied()
+    }
+
+    /// Cells energized by a beam entering at `start`. Beams all move one cell at a time, so the
+    /// closest one to a tile hits it first, and tiles keep whatever state the beams leave them in.
+    pub fn fire(&mut self, start: Start) -> HashSet<I64Vec2> {
+        let mut energized = HashSet::new();
+        let mut history = HashSet::new();
+
+        // Hash of every tile's state, updated as they change. It starts at 0 since only the
+        // changes during this run matter for finding loops.
+        let mut state = 0;
+
+        let mut queue = VecDeque::from([(start.position, start.beam)]);
+
+        while let Some((pos, beam)) = queue.pop_front() {
+            if !self.contains(pos) {
+                continue;
+            }
+
+            energized.insert(pos);
+
+            let Some(tile) = self.tiles.get_mut(&pos) else {
+                queue.push_back((pos + beam.delta(), beam));
+                continue;
+            };
+
+            if !history.insert((pos, beam, state)) {
+                continue;
+            }
+
+            let before = tile.state();
+            let exits = tile.hit(beam);
+            let after = tile.state();
+            if before != after {
+                state ^= tile_hash(pos, before) ^ tile_hash(pos, after);
+            }
+
+            for exit in exits {
+                match exit {
+                    Exit::Here(beam) => queue.push_back((pos + beam.delta(), beam)),
+                    Exit::Portal(beam) => {
+                        let Some(&other) = self.portals.get(&pos) else {
+                            continue;
+                        };
+
+                        energized.insert(other);
+                        queue.push_back((other + beam.delta(), beam));
+                    }
+                }
+            }
+        }
+
+        energized
+    }
+}
+
+/// Part of the contraption's state hash for one tile, which gets xor-ed in and out as it changes.
+fn tile_hash(pos: I64Vec2, state: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (pos, state).hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn right() -> Start {
+        Start {
+            position: I64Vec2::ZERO,
+            beam: Beam::new(false, true),
+        }
+    }
+
+    fn fire(input: &str) -> usize {
+        let mut contraption = Contraption::parse(input, &TileSet::extended()).unwrap();
+        contraption.fire(right()).len()
+    }
+
+    #[test]
+    fn puzzle_tiles() {
+        let input = christmas_tree::indoc! {r"
+            .|...\....
+            |.-.\.....
+            .....|-...
+            ........|.
+            ..........
+            .........\
+            ..../.\\..
+            .-.-/..|..
+            .|....-|.\
+            ..//.|....
+        "};
+
+        let mut contraption = Contraption::parse(input, &TileSet::puzzle()).unwrap();
+        assert_eq!(contraption.fire(right()).len(), 46);
+        assert_eq!(
+            Contraption::parse("..#", &TileSet::puzzle()).unwrap_err(),
+            ParseError::UnknownTile {
+                tile: '#',
+                position: I64Vec2::new(2, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn extended_tiles() {
+        assert_eq!(fire("..#.."), 3);
+        assert_eq!(fire(".>.<."), 4);
+
+        // Through the portal and down the other side
+        assert_eq!(fire("..1..\n.....\n...1\\\n....."), 6);
+        assert_eq!(
+            Contraption::parse("..1..", &TileSet::extended()).unwrap_err(),
+            ParseError::UnpairedPortal {
+                label: '1',
+                ends: 1
+            }
+        );
+
+        // Custom tiles: this one only turns beams down
+        let set = TileSet::extended().with('*', |_| {
+            #[derive(Debug)]
+            struct Down;
+
+            impl Optic for Down {
+                fn hit(&mut self, _: Beam) -> Vec<Exit> {
+                    vec![Exit::Here(Beam::new(true, true))]
+                }
+            }
+
+            Box::new(Down)
+        });
+        let mut contraption = Contraption::parse("..*\n...\n...", &set).unwrap();
+        assert_eq!(contraption.fire(right()).len(), 5);
+    }
+
+    #[test]
+    fn toggling_mirrors() {
+        // Goes up (and off the map) the first time, then down the second time
+        let mut contraption = Contraption::parse("..%\n...\n...", &TileSet::extended()).unwrap();
+
+        assert_eq!(contraption.fire(right()).len(), 3);
+        assert_eq!(contraption.fire(right()).len(), 5);
+        assert_eq!(contraption.fire(right()).len(), 3);
+    }
+
+    #[test]
+    fn several_toggling_mirrors() {
+        // The second beam going down through `-` comes after both mirrors toggled, so it goes
+        // right and down this time
+        assert_eq!(fire(".|&\n.-%\n..."), 7);
+    }
+
+    #[test]
+    fn unlinked_portal() {
+        // A portal end that isn't paired with anything, which absorbs beams
+        let set = TileSet::extended().with('*', |_| {
+            #[derive(Debug)]
+            struct Warp;
+
+            impl Optic for Warp {
+                fn hit(&mut self, beam: Beam) -> Vec<Exit> {
+                    vec![Exit::Portal(beam), Exit::Here(Beam::new(true, true))]
+                }
+            }
+
+            Box::new(Warp)
+        });
+        let mut contraption = Contraption::parse("..*\n...", &set).unwrap();
+        assert_eq!(contraption.fire(right()).len(), 4);
+    }
+}
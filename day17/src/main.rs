@@ -1,9 +1,14 @@
 use std::collections::{BinaryHeap, HashMap};
 
+use christmas_tree::{
+    render::Image,
+    tracing,
+    viz::{Color, Grid},
+};
 use glam::I64Vec2;
 use ndarray::{Array1, Array2};
 
-christmas_tree::day!(17);
+christmas_tree::day!(17, visualize, render);
 
 type Vec2 = I64Vec2;
 
@@ -24,6 +29,7 @@ struct Node {
     position: I64Vec2,
     direction: I64Vec2,
     moved_straight: i64,
+    previous: Option<Key>,
 }
 
 impl Ord for Node {
@@ -46,17 +52,20 @@ impl Node {
     }
 }
 
-fn solve(map: &Array2<i64>, min_straight: i64, max_straight: i64) -> i64 {
+/// Returns the cost of the best route and the positions it goes through.
+fn solve(map: &Array2<i64>, min_straight: i64, max_straight: i64) -> (i64, Vec<Vec2>) {
     let size = map.shape();
 
     let mut queue = BinaryHeap::new();
     let mut costs = HashMap::new();
+    let mut came_from = HashMap::<Key, Key>::new();
 
     queue.push(Node {
         cost: 0,
         position: Vec2::ZERO,
         direction: Vec2::X,
         moved_straight: 0,
+        previous: None,
     });
 
     while let Some(node) = queue.pop() {
@@ -65,12 +74,20 @@ fn solve(map: &Array2<i64>, min_straight: i64, max_straight: i64) -> i64 {
             position,
             direction,
             moved_straight,
+            previous,
         } = &node;
 
+        tracing::trace!(cost, %position, %direction, moved_straight, "Pop");
+
         if [position.y as usize + 1, position.x as usize + 1] == size
             && *moved_straight >= min_straight
         {
-            return *cost;
+            let mut path = vec![node.key()];
+            path.extend(std::iter::successors(*previous, |key| {
+                came_from.get(key).copied()
+            }));
+
+            return (*cost, path.iter().rev().map(|key| key.0).collect());
         }
 
         let is_better = |node: &Node, costs: &HashMap<Key, i64>| {
@@ -89,6 +106,9 @@ fn solve(map: &Array2<i64>, min_straight: i64, max_straight: i64) -> i64 {
         }
 
         costs.insert(node.key(), *cost);
+        if let Some(previous) = previous {
+            came_from.insert(node.key(), *previous);
+        }
 
         let mut insert = |direction, moved_straight| {
             let position: Vec2 = *position + direction;
@@ -104,6 +124,7 @@ fn solve(map: &Array2<i64>, min_straight: i64, max_straight: i64) -> i64 {
                 position,
                 direction,
                 moved_straight,
+                previous: Some(node.key()),
             };
 
             if is_better(&next, &costs) {
@@ -130,13 +151,35 @@ fn solve(map: &Array2<i64>, min_straight: i64, max_straight: i64) -> i64 {
 fn part1(input: &str) -> i64 {
     let map = parse(input);
 
-    solve(&map, 0, 3)
+    solve(&map, 0, 3).0
 }
 
 fn part2(input: &str) -> i64 {
     let map = parse(input);
 
-    solve(&map, 4, 10)
+    solve(&map, 4, 10).0
+}
+
+fn visualize(input: &str) {
+    let map = parse(input);
+
+    for (part, (min_straight, max_straight)) in [(1, (0, 3)), (2, (4, 10))] {
+        let (cost, path) = solve(&map, min_straight, max_straight);
+
+        println!("Part {part}: {cost}");
+        Grid::from_text(input).path(path, Color::Red).print();
+    }
+}
+
+/// Route of part 2 (which is the more interesting one)
+fn render(input: &str) -> Image {
+    let map = parse(input);
+    let (_, path) = solve(&map, 4, 10);
+
+    let mut grid = Grid::from_text(input);
+    grid.path(path, Color::Red);
+
+    Image::Grid(grid)
 }
 
 christmas_tree::examples! {
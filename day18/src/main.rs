@@ -1,6 +1,7 @@
+use christmas_tree::render::{Image, Svg};
 use rayon::prelude::*;
 
-christmas_tree::day!(18);
+christmas_tree::day!(18, render);
 
 type Vec2 = glam::I64Vec2;
 
@@ -206,6 +207,29 @@ fn part2(input: &str) -> i64 {
         .area()
 }
 
+/// The part 1 trench, with each edge painted with its color.
+fn render(input: &str) -> Image {
+    let mut svg = Svg::new();
+    let mut vertices = vec![[0.0, 0.0]];
+    let mut edges = Vec::new();
+
+    let mut position = Vec2::ZERO;
+    for instruction in input.lines().map(|line| parser::instruction(line).unwrap()) {
+        let origin = position;
+        position += instruction.direction.delta() * instruction.distance;
+
+        vertices.push(position.as_dvec2().into());
+        edges.push((origin, position, format!("#{:06x}", instruction.color)));
+    }
+
+    svg.polygon(vertices, "#303030", "none");
+    for (from, to, color) in edges {
+        svg.line(from.as_dvec2().into(), to.as_dvec2().into(), &color);
+    }
+
+    Image::Svg(svg)
+}
+
 christmas_tree::examples! {
     "
         R 6 (#70c710)
@@ -1,5 +1,7 @@
 use std::collections::{HashMap, VecDeque};
 
+use christmas_tree::tracing;
+
 christmas_tree::day!(20);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -93,6 +95,7 @@ fn press_button(map: &mut HashMap<&str, Module<'_>>, mut f: impl FnMut(&str, Pul
     let mut queue = VecDeque::from([("", "broadcaster", Pulse::Low)]);
 
     while let Some((from, name, pulse)) = queue.pop_front() {
+        tracing::trace!(from, to = name, ?pulse);
         f(name, pulse);
 
         let Some(module) = map.get_mut(name) else {
@@ -137,11 +140,12 @@ fn part1(input: &str) -> i64 {
 
     let mut count = [0, 0];
 
-    for _ in 0..1000 {
+    for press in 1..=1000 {
+        let _span = tracing::debug_span!("press", press).entered();
         press_button(&mut map, |_name, pulse| count[pulse as usize] += 1);
     }
 
-    dbg!(&count);
+    tracing::debug!(low = count[0], high = count[1]);
 
     count.iter().product()
 }
@@ -162,9 +166,12 @@ fn part2(input: &str) -> i64 {
         .collect::<HashMap<_, _>>();
 
     for i in 1.. {
+        let _span = tracing::debug_span!("press", press = i).entered();
+
         press_button(&mut map, |name, pulse| {
             if let Some(first_press) = until_parents.get_mut(name) {
                 if *first_press == 0 && pulse == Pulse::Low {
+                    tracing::debug!(name, "First low pulse");
                     *first_press = i;
                 }
             }
@@ -1,6 +1,11 @@
-use std::{collections::HashSet, mem};
+use std::{
+    collections::{HashSet, VecDeque},
+    mem,
+};
 
-christmas_tree::day!(21);
+use christmas_tree::polynomial::{Polynomial, Rational};
+
+christmas_tree::day!(21, explain);
 
 type Vec2 = glam::I64Vec2;
 
@@ -80,33 +85,241 @@ fn count_locations(walls: &HashSet<Vec2>, start: Vec2, steps: i64, size: i64) ->
     count
 }
 
-fn part2(input: &str) -> i64 {
-    const STEPS: i64 = 26_501_365;
+const STEPS: i64 = 26_501_365;
+
+/// Step counts that end at the edge of a tile: half a tile to get out of the starting one, and
+/// then whole tiles after that.
+fn sample_points(size: i64, count: i64) -> Vec<i64> {
+    (0..count).map(|i| (i * 2 + 1) * size / 2).collect()
+}
 
+fn samples(input: &str, count: i64) -> Vec<(i128, i128)> {
     let (walls, start, size) = parse(input);
 
-    let x = [0, 1, 2].map(|i| (i * 2 + 1) * size / 2);
-    let y = x.map(|x| count_locations(&walls, start, x, size) as f64);
-
-    // Lagrange polynomial
-    let result = (0..3)
-        .map(|i| {
-            (0..3)
-                .filter(|&j| i != j)
-                .map(|j| {
-                    let num = STEPS - x[j];
-                    let den = x[i] - x[j];
-
-                    num as f64 / den as f64
-                })
-                .product::<f64>()
-                * y[i]
+    sample_points(size, count)
+        .into_iter()
+        .map(|x| (x as i128, count_locations(&walls, start, x, size) as i128))
+        .collect()
+}
+
+/// The amount of reachable plots grows quadratically every time the steps reach another ring of
+/// tiles (as long as the row and column of the start are clear), so three samples are enough.
+fn quadratic(samples: &[(i128, i128)]) -> Polynomial {
+    Polynomial::interpolate(&samples[..3]).expect("Overflow while interpolating")
+}
+
+/// Amount of samples past the three used for the interpolation that have to match the quadratic.
+const VERIFIED: i64 = 2;
+
+/// Extrapolates the quadratic after checking it against a couple more brute forced samples.
+/// `None` if it doesn't match them, or doesn't give a whole number of plots.
+fn part2_quadratic(input: &str) -> Option<i64> {
+    let samples = samples(input, 3 + VERIFIED);
+    let polynomial = quadratic(&samples);
+
+    if !fits(&polynomial, &samples) {
+        return None;
+    }
+
+    let result = polynomial.checked_eval(Rational::from(STEPS as i128))?;
+    if !result.is_integer() {
+        return None;
+    }
+
+    i64::try_from(result.to_integer()).ok()
+}
+
+fn fits(polynomial: &Polynomial, samples: &[(i128, i128)]) -> bool {
+    samples.iter().all(|&(x, count)| {
+        polynomial.checked_eval(Rational::from(x)) == Some(Rational::from(count))
+    })
+}
+
+/// Prints how the samples compare to the quadratic, including `extra` ones past the three used
+/// for the interpolation.
+fn verify(input: &str, extra: i64) -> bool {
+    let samples = samples(input, 3 + extra);
+    let polynomial = quadratic(&samples);
+
+    println!("Plots after x steps: {polynomial}");
+
+    for &(x, count) in &samples {
+        let predicted = polynomial.eval(Rational::from(x));
+        let matches = predicted == Rational::from(count);
+
+        println!(
+            "  {x:>4} steps: {count:>8} plots, predicted {predicted:>8} {}",
+            if matches { "✓" } else { "✗" }
+        );
+    }
+
+    fits(&polynomial, &samples)
+}
+
+/// Shortest distances from the start to every plot in the tiles around the starting one, up to
+/// `radius` tiles away in each direction. Tile `(0, 0)` is the one with the start.
+struct TiledDistances {
+    size: i64,
+    radius: i64,
+    distances: Vec<Option<i64>>,
+}
+
+impl TiledDistances {
+    fn new(walls: &HashSet<Vec2>, start: Vec2, size: i64, radius: i64) -> Self {
+        let width = (2 * radius + 1) * size;
+        let offset = Vec2::splat(radius * size);
+        let index = |position: Vec2| (position.y * width + position.x) as usize;
+
+        let mut distances = vec![None; (width * width) as usize];
+        let mut queue = VecDeque::from([(start + offset, 0)]);
+        distances[index(start + offset)] = Some(0);
+
+        while let Some((position, distance)) = queue.pop_front() {
+            for dir in DIRECTIONS {
+                let next = position + dir;
+                let inside = (0..width).contains(&next.x) && (0..width).contains(&next.y);
+                let original = Vec2::new(next.x.rem_euclid(size), next.y.rem_euclid(size));
+
+                if inside && !walls.contains(&original) && distances[index(next)].is_none() {
+                    distances[index(next)] = Some(distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        Self {
+            size,
+            radius,
+            distances,
+        }
+    }
+
+    fn get(&self, tile: Vec2, plot: Vec2) -> Option<i64> {
+        let width = (2 * self.radius + 1) * self.size;
+        let position = (tile + self.radius) * self.size + plot;
+        self.distances[(position.y * width + position.x) as usize]
+    }
+
+    /// Far enough from the start, every step towards the outside adds exactly `size` steps to the
+    /// distance of each plot, which is what lets [`count_tiled`] extrapolate past `radius`.
+    fn is_stable(&self) -> bool {
+        let r = self.radius;
+        let mut outer = (-r..=r).flat_map(|i| {
+            [
+                (Vec2::new(r, i), Vec2::new(r - 1, i)),
+                (Vec2::new(-r, i), Vec2::new(1 - r, i)),
+                (Vec2::new(i, r), Vec2::new(i, r - 1)),
+                (Vec2::new(i, -r), Vec2::new(i, 1 - r)),
+            ]
+        });
+
+        outer.all(|(tile, inner)| {
+            plots(self.size).all(|plot| match (self.get(tile, plot), self.get(inner, plot)) {
+                (Some(a), Some(b)) => a - b == self.size,
+                (a, b) => a.is_none() && b.is_none(),
+            })
         })
-        .sum::<f64>();
+    }
+}
+
+fn plots(size: i64) -> impl Iterator<Item = Vec2> {
+    (0..size).flat_map(move |y| (0..size).map(move |x| Vec2::new(x, y)))
+}
+
+/// Amount of `k >= 0` for which the plot is reached in exactly `steps` (or an even amount less)
+/// when it's at `distance + k * size`. Every `k` is counted `k + 1` times when `corner` is set,
+/// since there are `k + 1` corner tiles that are `k` tiles away diagonally.
+fn count_repeated(distance: i64, steps: i64, size: i64, corner: bool) -> i64 {
+    if distance > steps {
+        return 0;
+    }
 
-    assert!((result.floor() - result).abs() <= 0.001);
+    let max = (steps - distance) / size;
 
-    result.floor() as i64
+    // The valid k are `first, first + stride, ...` up to `max`
+    let (first, stride) = if size % 2 == 0 {
+        if (steps - distance) % 2 != 0 {
+            return 0;
+        }
+
+        (0, 1)
+    } else {
+        ((steps - distance) % 2, 2)
+    };
+
+    if first > max {
+        return 0;
+    }
+
+    let n = (max - first) / stride + 1;
+
+    if corner {
+        // Sum of k + 1 over the arithmetic sequence
+        n * (first + 1) + stride * n * (n - 1) / 2
+    } else {
+        n
+    }
+}
+
+/// Plots reachable in exactly `steps` on the infinitely tiled map, without assuming anything about
+/// the map. The tiles near the start are counted directly. Past them, the tiles straight out from
+/// an edge tile repeat its distances plus `size` each time, and the tiles diagonally out from a
+/// corner tile do the same for every tile they are away from it.
+fn count_tiled(walls: &HashSet<Vec2>, start: Vec2, steps: i64, size: i64) -> i64 {
+    let mut radius = 2;
+    let distances = loop {
+        let distances = TiledDistances::new(walls, start, size, radius);
+        if distances.is_stable() {
+            break distances;
+        }
+
+        radius += 1;
+        assert!(radius <= 32, "Distances between tiles never stabilize");
+    };
+
+    let mut count = 0;
+
+    for ty in -radius..=radius {
+        for tx in -radius..=radius {
+            let edges = (tx.abs() == radius) as u8 + (ty.abs() == radius) as u8;
+
+            for plot in plots(size) {
+                let Some(distance) = distances.get(Vec2::new(tx, ty), plot) else {
+                    continue;
+                };
+
+                count += match edges {
+                    0 => (distance <= steps && distance % 2 == steps % 2) as i64,
+                    1 => count_repeated(distance, steps, size, false),
+                    _ => count_repeated(distance, steps, size, true),
+                };
+            }
+        }
+    }
+
+    count
+}
+
+fn part2(input: &str) -> i64 {
+    let (walls, start, size) = parse(input);
+
+    count_tiled(&walls, start, STEPS, size)
+}
+
+fn explain(input: &str) {
+    let tiled = part2(input);
+
+    verify(input, VERIFIED);
+    match part2_quadratic(input) {
+        Some(plots) => {
+            println!("The growth is quadratic, so after {STEPS} steps there are {plots} plots")
+        }
+        None => {
+            println!("The growth isn't quadratic, so extrapolating doesn't work for this input")
+        }
+    }
+
+    println!("Counting by tiles there are {tiled} plots");
 }
 
 #[cfg(test)]
@@ -129,4 +342,49 @@ mod tests {
     fn part1() {
         assert_eq!(super::solve1(TEST_INPUT, 6), 16);
     }
+
+    #[test]
+    fn open_garden_is_quadratic() {
+        let mut input = ".........\n".repeat(9);
+        input.replace_range(4 * 10 + 4..4 * 10 + 5, "S");
+
+        assert!(super::verify(&input, super::VERIFIED));
+        assert_eq!(
+            super::part2_quadratic(&input),
+            Some((super::STEPS + 1).pow(2))
+        );
+
+        // The example's start has rocks in its row and column
+        assert_eq!(super::part2_quadratic(TEST_INPUT), None);
+        assert_eq!(super::part2(&input), (super::STEPS + 1).pow(2));
+    }
+
+    #[test]
+    fn tiled_matches_puzzle() {
+        let (walls, start, size) = super::parse(TEST_INPUT);
+
+        for (steps, expected) in [
+            (6, 16),
+            (10, 50),
+            (50, 1594),
+            (100, 6536),
+            (500, 167004),
+            (1000, 668697),
+            (5000, 16733044),
+        ] {
+            assert_eq!(
+                super::count_tiled(&walls, start, steps, size),
+                expected,
+                "{steps} steps"
+            );
+        }
+
+        for steps in [0, 1, 7, 11, 33, 64] {
+            assert_eq!(
+                super::count_tiled(&walls, start, steps, size),
+                super::count_locations(&walls, start, steps, size),
+                "{steps} steps"
+            );
+        }
+    }
 }
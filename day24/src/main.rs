@@ -1,6 +1,7 @@
+use christmas_tree::render::{Image, Svg};
 use nalgebra::{vector, Matrix3, Matrix6};
 
-christmas_tree::day!(24);
+christmas_tree::day!(24, render);
 
 type Vec3 = nalgebra::Vector3<i64>;
 
@@ -147,6 +148,43 @@ fn part2(input: &str) -> i64 {
 
     panic!("No solution found");
 }
+
+/// Hailstone trajectories (projected to xy) through the part 1 test area.
+fn render(input: &str) -> Image {
+    const MIN: f64 = 200_000_000_000_000.0;
+    const MAX: f64 = 400_000_000_000_000.0;
+
+    let lines = input.lines().map(|line| parser::line(line).unwrap());
+
+    let mut svg = Svg::new();
+    svg.rect([MIN, MIN], [MAX, MAX], "#202020", "#808080");
+
+    for line in lines {
+        let pos = [line.pos.x as f64, line.pos.y as f64];
+        let vel = [line.vel.x as f64, line.vel.y as f64];
+
+        // Time until the hailstone leaves the test area through one of the sides
+        let exit = (0..2)
+            .filter(|&i| vel[i] != 0.0)
+            .map(|i| {
+                let bound = if vel[i] > 0.0 { MAX } else { MIN };
+                (bound - pos[i]) / vel[i]
+            })
+            .fold(f64::INFINITY, f64::min);
+
+        if exit < 0.0 {
+            continue;
+        }
+
+        let end = [pos[0] + vel[0] * exit, pos[1] + vel[1] * exit];
+
+        svg.line(pos, end, "#4080ff")
+            .circle(pos, (MAX - MIN) / 400.0, "#ff4040");
+    }
+
+    Image::Svg(svg)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;